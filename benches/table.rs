@@ -0,0 +1,164 @@
+// Insert/get/remove for each hasher the table ships with, plus std's
+// RandomState as a reference point. Throughput is reported in keys per
+// second, so a slower hash (or worse collisions) shows up directly.
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::hint::black_box;
+
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use hash_table::{Djb2State, Fnv1aState, HashS2T, MulMixState};
+
+const SIZES: [usize; 2] = [1_000, 10_000];
+
+// Random words of 4 to 16 letters starting at `first` ('a' for the keys
+// that get inserted, 'A' for the ones that are never there). Xorshift
+// with a fixed seed, so every run and every hasher sees the same keys.
+fn random_keys(n: usize, first: u8) -> Vec<String> {
+    let mut state = 0x9e3779b97f4a7c15u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..n)
+        .map(|_| {
+            let len = 4 + (next() % 13) as usize;
+            (0..len)
+                .map(|_| char::from(first + (next() % 26) as u8))
+                .collect()
+        })
+        .collect()
+}
+
+fn filled<S: BuildHasher>(
+    new: impl Fn() -> HashS2T<String, usize, S>,
+    keys: &[String],
+) -> HashS2T<String, usize, S> {
+    let mut h = new();
+    for (i, k) in keys.iter().enumerate() {
+        h.insert(k.clone(), i);
+    }
+    h
+}
+
+// Runs a bench once per hasher, each one building its own kind of table
+macro_rules! for_each_hasher {
+    ($bench:ident($($arg:expr),*)) => {
+        $bench("char_sum", HashS2T::new, $($arg),*);
+        $bench("mul_mix", || HashS2T::with_hasher(MulMixState::default()), $($arg),*);
+        $bench("fnv1a", || HashS2T::with_hasher(Fnv1aState::default()), $($arg),*);
+        $bench("djb2", || HashS2T::with_hasher(Djb2State::default()), $($arg),*);
+        $bench("seeded", || HashS2T::with_seed(0x5eed), $($arg),*);
+        $bench("std", || HashS2T::with_hasher(RandomState::new()), $($arg),*);
+        #[cfg(feature = "ahash")]
+        $bench("ahash", HashS2T::ahash, $($arg),*);
+    };
+}
+
+fn bench_insert<S: BuildHasher>(
+    name: &str,
+    new: impl Fn() -> HashS2T<String, usize, S>,
+    group: &mut BenchmarkGroup<WallTime>,
+    keys: &[String],
+) {
+    group.bench_with_input(BenchmarkId::new(name, keys.len()), keys, |b, keys| {
+        // cloning the keys and dropping the table stay out of the timing
+        b.iter_batched(
+            || (new(), keys.to_vec()),
+            |(mut h, keys)| {
+                for (i, k) in keys.into_iter().enumerate() {
+                    h.insert(k, i);
+                }
+                h
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_get<S: BuildHasher>(
+    name: &str,
+    new: impl Fn() -> HashS2T<String, usize, S>,
+    group: &mut BenchmarkGroup<WallTime>,
+    keys: &[String],
+    lookups: &[String],
+) {
+    let h = filled(new, keys);
+    group.bench_with_input(BenchmarkId::new(name, keys.len()), lookups, |b, lookups| {
+        b.iter(|| {
+            lookups
+                .iter()
+                .filter(|k| h.get(black_box(k.as_str())).is_some())
+                .count()
+        })
+    });
+}
+
+fn bench_remove<S: BuildHasher + Clone>(
+    name: &str,
+    new: impl Fn() -> HashS2T<String, usize, S>,
+    group: &mut BenchmarkGroup<WallTime>,
+    keys: &[String],
+) {
+    let h = filled(new, keys);
+    group.bench_with_input(BenchmarkId::new(name, keys.len()), keys, |b, keys| {
+        b.iter_batched(
+            || h.clone(),
+            |mut h| {
+                for k in keys {
+                    black_box(h.remove(k.as_str()));
+                }
+                h
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert");
+    for n in SIZES {
+        let keys = random_keys(n, b'a');
+        group.throughput(Throughput::Elements(n as u64));
+        for_each_hasher!(bench_insert(&mut group, &keys));
+    }
+    group.finish();
+}
+
+fn get_hit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_hit");
+    for n in SIZES {
+        let keys = random_keys(n, b'a');
+        group.throughput(Throughput::Elements(n as u64));
+        for_each_hasher!(bench_get(&mut group, &keys, &keys));
+    }
+    group.finish();
+}
+
+fn get_miss(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_miss");
+    for n in SIZES {
+        let keys = random_keys(n, b'a');
+        let missing = random_keys(n, b'A');
+        group.throughput(Throughput::Elements(n as u64));
+        for_each_hasher!(bench_get(&mut group, &keys, &missing));
+    }
+    group.finish();
+}
+
+fn remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("remove");
+    for n in SIZES {
+        let keys = random_keys(n, b'a');
+        group.throughput(Throughput::Elements(n as u64));
+        for_each_hasher!(bench_remove(&mut group, &keys));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, insert, get_hit, get_miss, remove);
+criterion_main!(benches);
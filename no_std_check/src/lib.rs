@@ -0,0 +1,26 @@
+// Uses the table from a no_std crate, so building this one (on its own,
+// `cargo build -p no-std-check`, or other workspace members would turn
+// `std` back on) fails if the core of the table starts depending on std
+#![no_std]
+
+use hash_table::{hashs2t, HashS2T, OpenAddr};
+
+// How many times the most repeated word shows up
+pub fn max_word_count(text: &str) -> usize {
+    let mut counts = HashS2T::new();
+    for w in text.split_whitespace() {
+        *counts.entry(w).or_default() += 1;
+    }
+    counts.values().copied().max().unwrap_or(0)
+}
+
+pub fn open_addr_roundtrip(k: u32) -> Option<u32> {
+    let mut h = OpenAddr::new();
+    h.insert(k, k * 2);
+    h.remove(&k)
+}
+
+pub fn from_macro() -> usize {
+    let h = hashs2t! { "Woffo" => 1, "Gato" => 2 };
+    h.len()
+}
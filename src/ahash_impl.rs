@@ -0,0 +1,35 @@
+// ahash as the table's hasher, for when it's wanted fast rather than
+// readable. Its RandomState picks new keys for every table, like std's.
+use ahash::RandomState;
+
+use crate::HashS2T;
+
+impl<K, V> HashS2T<K, V, RandomState> {
+    pub fn ahash() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fewer_collisions() {
+        let words = || include_str!("../testdata/words.txt").split_whitespace();
+        let mut char_sum = HashS2T::new();
+        let mut ahash = HashS2T::ahash();
+        for (v, k) in words().enumerate() {
+            char_sum.insert(k, v);
+            ahash.insert(k, v);
+        }
+        assert_eq!(char_sum.capacity(), ahash.capacity());
+        let (fast, slow) = (ahash.stats().collisions, char_sum.stats().collisions);
+        // about 160 against 586: most of what ahash has left is down to the
+        // load factor, not the hash
+        assert!(fast * 3 < slow, "ahash: {fast}, char sum: {slow}");
+        for (v, k) in words().enumerate() {
+            assert_eq!(ahash.get(k), Some(&v));
+        }
+    }
+}
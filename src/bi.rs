@@ -0,0 +1,201 @@
+// A one-to-one map, looked up by key or by value. A second table maps
+// every value back to its key; both are kept in step, so a value is never
+// held by two keys and a key never points at a value it doesn't hold.
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use crate::{CharSumState, HashS2T};
+
+pub struct BiHashS2T<K, V, S = CharSumState> {
+    forward: HashS2T<K, V, S>,
+    reverse: HashS2T<V, K, S>,
+}
+
+impl<K, V, S> Default for BiHashS2T<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self {
+            forward: HashS2T::default(),
+            reverse: HashS2T::default(),
+        }
+    }
+}
+
+impl<K, V> BiHashS2T<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, S> BiHashS2T<K, V, S> {
+    // Both directions hash with a copy of hash_builder
+    pub fn with_hasher(hash_builder: S) -> Self
+    where
+        S: Clone,
+    {
+        Self {
+            forward: HashS2T::with_hasher(hash_builder.clone()),
+            reverse: HashS2T::with_hasher(hash_builder),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.forward.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.forward.iter().map(|item| (item.key(), item.value()))
+    }
+}
+
+impl<K, V, S> BiHashS2T<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    // Returns the value k held before. v is taken away from any other key
+    // holding it, and that key goes with it: afterwards k is the only key
+    // for v.
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if let Some(other) = self.reverse.remove(&v) {
+            if other != k {
+                self.forward.remove(&other);
+            }
+        }
+        let old = self.forward.insert(k.clone(), v.clone());
+        // the old value is orphaned, unless it was v all along
+        if let Some(old) = &old {
+            if *old != v {
+                self.reverse.remove(old);
+            }
+        }
+        self.reverse.insert(v, k);
+        old
+    }
+
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.forward.get(k)
+    }
+
+    pub fn get_by_value<Q>(&self, v: &Q) -> Option<&K>
+    where
+        V: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.reverse.get(v)
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.forward.contains_key(k)
+    }
+
+    pub fn contains_value<Q>(&self, v: &Q) -> bool
+    where
+        V: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.reverse.contains_key(v)
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let v = self.forward.remove(k)?;
+        self.reverse.remove(&v);
+        Some(v)
+    }
+
+    pub fn remove_by_value<Q>(&mut self, v: &Q) -> Option<K>
+    where
+        V: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let k = self.reverse.remove(v)?;
+        self.forward.remove(&k);
+        Some(k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Every entry is in both tables, pointing at each other
+    fn check_consistent<K, V>(h: &BiHashS2T<K, V>)
+    where
+        K: Hash + Eq + Clone + core::fmt::Debug,
+        V: Hash + Eq + Clone + core::fmt::Debug,
+    {
+        assert_eq!(h.forward.len(), h.reverse.len());
+        for (k, v) in h.iter() {
+            assert_eq!(h.get_by_value(v), Some(k));
+        }
+    }
+
+    #[test]
+    fn insert_both_ways() {
+        let mut h = BiHashS2T::new();
+        assert_eq!(h.insert("Woffo", "dog"), None);
+        assert_eq!(h.insert("Gato", "cat"), None);
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("Woffo"), Some(&"dog"));
+        assert_eq!(h.get_by_value("cat"), Some(&"Gato"));
+        assert_eq!(h.get_by_value("fish"), None);
+        assert!(h.contains_value("dog"));
+        check_consistent(&h);
+    }
+
+    #[test]
+    fn overwrite_drops_old_value() {
+        let mut h = BiHashS2T::new();
+        h.insert("Woffo", "dog");
+        assert_eq!(h.insert("Woffo", "puppy"), Some("dog"));
+        assert_eq!(h.get_by_value("dog"), None);
+        assert_eq!(h.get_by_value("puppy"), Some(&"Woffo"));
+        // same pair again changes nothing
+        assert_eq!(h.insert("Woffo", "puppy"), Some("puppy"));
+        assert_eq!(h.len(), 1);
+        check_consistent(&h);
+
+        // a value moving to another key takes the old key away
+        h.insert("Gato", "cat");
+        assert_eq!(h.insert("Michi", "cat"), None);
+        assert_eq!(h.get_by_value("cat"), Some(&"Michi"));
+        assert!(!h.contains_key("Gato"));
+        assert_eq!(h.len(), 2);
+        check_consistent(&h);
+    }
+
+    #[test]
+    fn remove_both_ways() {
+        let mut h = BiHashS2T::new();
+        h.insert("Woffo", "dog");
+        h.insert("Gato", "cat");
+        assert_eq!(h.remove("Woffo"), Some("dog"));
+        assert!(!h.contains_value("dog"));
+        assert_eq!(h.remove("Woffo"), None);
+
+        assert_eq!(h.remove_by_value("cat"), Some("Gato"));
+        assert!(!h.contains_key("Gato"));
+        assert_eq!(h.remove_by_value("cat"), None);
+        assert!(h.is_empty());
+        check_consistent(&h);
+    }
+}
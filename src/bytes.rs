@@ -0,0 +1,139 @@
+// A table keyed by byte strings, for binary keys that aren't valid UTF-8.
+// Keys are stored as Cow<'static, [u8]>: copied when inserted from a
+// borrowed slice, kept borrowed when they are 'static already.
+use alloc::borrow::Cow;
+use core::hash::BuildHasher;
+
+use crate::{CharSumState, HashS2T};
+
+pub struct HashS2TBytes<V, S = CharSumState> {
+    table: HashS2T<Cow<'static, [u8]>, V, S>,
+}
+
+impl<V, S> Default for HashS2TBytes<V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<V> HashS2TBytes<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            table: HashS2T::with_capacity(n),
+        }
+    }
+}
+
+impl<V, S> HashS2TBytes<V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            table: HashS2T::with_hasher(hash_builder),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], &V)> {
+        self.table
+            .iter()
+            .map(|item| (item.key().as_ref(), item.value()))
+    }
+}
+
+// The key is hashed as a byte slice, so a copied key and a borrowed one
+// with the same bytes are the same key
+impl<V, S> HashS2TBytes<V, S>
+where
+    S: BuildHasher,
+{
+    // The key is copied; see insert_static to keep it borrowed
+    pub fn insert(&mut self, k: &[u8], v: V) -> Option<V> {
+        self.table.insert(Cow::Owned(k.to_vec()), v)
+    }
+
+    pub fn insert_static(&mut self, k: &'static [u8], v: V) -> Option<V> {
+        self.table.insert(Cow::Borrowed(k), v)
+    }
+
+    pub fn get(&self, k: &[u8]) -> Option<&V> {
+        self.table.get(k)
+    }
+
+    pub fn get_mut(&mut self, k: &[u8]) -> Option<&mut V> {
+        self.table.get_mut(k)
+    }
+
+    pub fn contains_key(&self, k: &[u8]) -> bool {
+        self.table.contains_key(k)
+    }
+
+    pub fn remove(&mut self, k: &[u8]) -> Option<V> {
+        self.table.remove(k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn non_utf8_keys() {
+        let keys: [&[u8]; 4] = [b"\xff\xfe", b"\xc3", b"\x80abc", b"\xed\xa0\x80"];
+        for k in keys {
+            assert!(core::str::from_utf8(k).is_err());
+        }
+
+        let mut h = HashS2TBytes::new();
+        for (i, k) in keys.iter().enumerate() {
+            assert_eq!(h.insert(k, i), None);
+        }
+        assert_eq!(h.insert(b"\xc3", 10), Some(1));
+        assert_eq!(h.len(), 4);
+        assert_eq!(h.get(b"\xff\xfe"), Some(&0));
+        assert_eq!(h.get(b"\xfe\xff"), None);
+        assert_eq!(h.get(b"\xc3"), Some(&10));
+        // same byte sum as "\x80abc", different bytes
+        assert_eq!(h.get(b"\x81abb"), None);
+
+        *h.get_mut(b"\x80abc").unwrap() += 1;
+        assert_eq!(h.remove(b"\x80abc"), Some(3));
+        assert!(!h.contains_key(b"\x80abc"));
+        let mut left: Vec<_> = h.iter().map(|(k, v)| (k.to_vec(), *v)).collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [
+                (b"\xc3".to_vec(), 10),
+                (b"\xed\xa0\x80".to_vec(), 3),
+                (b"\xff\xfe".to_vec(), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn static_keys_stay_borrowed() {
+        static KEY: &[u8] = b"\xde\xad\xbe\xef";
+        let mut h = HashS2TBytes::new();
+        h.insert_static(KEY, 1);
+        // insert copies the same bytes, and finds (and overwrites) it
+        assert_eq!(h.insert(KEY, 2), Some(1));
+
+        h.insert_static(b"\x01", 3);
+        let key = h.table.keys().find(|k| k.as_ref() == b"\x01").unwrap();
+        assert!(matches!(key, Cow::Borrowed(_)));
+        assert_eq!(h.get(KEY), Some(&2));
+    }
+}
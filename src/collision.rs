@@ -0,0 +1,244 @@
+// How a bucket holds the items hashed to it. The table only ever reaches
+// a bucket through this trait, so the way collisions are resolved can be
+// swapped without touching insert/get/remove: a Vec (the default) keeps
+// the items packed together, a LinkedList allocates a node per item.
+use alloc::collections::linked_list::{self, LinkedList};
+use alloc::vec::Vec;
+
+use crate::HashItem;
+
+pub trait CollisionStrategy<K, V>:
+    Default + IntoIterator<Item = HashItem<K, V>, IntoIter: DoubleEndedIterator>
+{
+    type Iter<'a>: DoubleEndedIterator<Item = &'a HashItem<K, V>>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+    type IterMut<'a>: Iterator<Item = &'a mut HashItem<K, V>>
+    where
+        Self: 'a,
+        K: 'a,
+        V: 'a;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn iter(&self) -> Self::Iter<'_>;
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_>;
+
+    // The item's key must not be in the bucket yet
+    fn push(&mut self, item: HashItem<K, V>) -> &mut HashItem<K, V>;
+
+    // Takes out the first item f returns true for; the order of the rest
+    // may change
+    fn remove_where<F>(&mut self, f: F) -> Option<HashItem<K, V>>
+    where
+        F: FnMut(&HashItem<K, V>) -> bool;
+
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut HashItem<K, V>) -> bool;
+
+    // Gives back unused capacity, for strategies that keep some
+    fn shrink_to_fit(&mut self) {}
+}
+
+impl<K, V> CollisionStrategy<K, V> for Vec<HashItem<K, V>> {
+    type Iter<'a>
+        = core::slice::Iter<'a, HashItem<K, V>>
+    where
+        K: 'a,
+        V: 'a;
+    type IterMut<'a>
+        = core::slice::IterMut<'a, HashItem<K, V>>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        self.as_slice().iter()
+    }
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        self.as_mut_slice().iter_mut()
+    }
+
+    fn push(&mut self, item: HashItem<K, V>) -> &mut HashItem<K, V> {
+        let pos = Vec::len(self);
+        Vec::push(self, item);
+        &mut self[pos]
+    }
+
+    fn remove_where<F>(&mut self, f: F) -> Option<HashItem<K, V>>
+    where
+        F: FnMut(&HashItem<K, V>) -> bool,
+    {
+        let pos = self.as_slice().iter().position(f)?;
+        // order inside a bucket does not matter
+        Some(self.swap_remove(pos))
+    }
+
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut HashItem<K, V>) -> bool,
+    {
+        self.retain_mut(f);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        Vec::shrink_to_fit(self);
+    }
+}
+
+impl<K, V> CollisionStrategy<K, V> for LinkedList<HashItem<K, V>> {
+    type Iter<'a>
+        = linked_list::Iter<'a, HashItem<K, V>>
+    where
+        K: 'a,
+        V: 'a;
+    type IterMut<'a>
+        = linked_list::IterMut<'a, HashItem<K, V>>
+    where
+        K: 'a,
+        V: 'a;
+
+    fn len(&self) -> usize {
+        LinkedList::len(self)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        LinkedList::iter(self)
+    }
+
+    fn iter_mut(&mut self) -> Self::IterMut<'_> {
+        LinkedList::iter_mut(self)
+    }
+
+    fn push(&mut self, item: HashItem<K, V>) -> &mut HashItem<K, V> {
+        self.push_back(item);
+        self.back_mut().expect("just pushed")
+    }
+
+    fn remove_where<F>(&mut self, f: F) -> Option<HashItem<K, V>>
+    where
+        F: FnMut(&HashItem<K, V>) -> bool,
+    {
+        // no stable way to unlink a node in the middle: split the list
+        // there and join the halves back without it
+        let pos = LinkedList::iter(self).position(f)?;
+        let mut tail = self.split_off(pos);
+        let item = tail.pop_front();
+        self.append(&mut tail);
+        item
+    }
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut HashItem<K, V>) -> bool,
+    {
+        for mut item in core::mem::take(self) {
+            if f(&mut item) {
+                self.push_back(item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{CharSumState, HashS2T};
+
+    // Every strategy has to pass this: same results, same stats, whatever
+    // the bucket looks like inside
+    fn behaves_like_a_map<B: CollisionStrategy<&'static str, i32> + Clone>() {
+        let mut h: HashS2T<&str, i32, CharSumState, B> = HashS2T::default();
+        assert_eq!(h.insert("Woffo", 1), None);
+        // "ab" and "ba" share a bucket with the byte-sum hash
+        assert_eq!(h.insert("ab", 2), None);
+        assert_eq!(h.insert("ba", 3), None);
+        assert_eq!(h.insert("ab", 4), Some(2));
+        assert_eq!(h.len(), 3);
+        assert_eq!(h.get("ab"), Some(&4));
+        assert_eq!(h.get("ba"), Some(&3));
+        assert_eq!(h.get("Pez"), None);
+        assert_eq!(h.stats().collisions, 1);
+        assert_eq!(h.stats().max_bucket_len, 2);
+
+        *h.get_mut("Woffo").unwrap() += 10;
+        *h.entry("Gato").or_insert(0) += 5;
+        h.entry("ba").and_modify(|v| *v *= 2);
+        assert_eq!(h["Woffo"], 11);
+        assert_eq!(h["Gato"], 5);
+        assert_eq!(h["ba"], 6);
+
+        assert_eq!(h.remove("ab"), Some(4));
+        assert_eq!(h.remove("ab"), None);
+        assert_eq!(h.stats().collisions, 0);
+        assert_eq!(h.remove_entry("ba"), Some(("ba", 6)));
+
+        // enough to resize a few times
+        for (i, w) in ["a", "b", "c", "d", "e", "f", "g", "h"].iter().enumerate() {
+            h.insert(w, i as i32);
+        }
+        h.reserve(1000);
+        assert_eq!(h.len(), 10);
+        assert_eq!(h.iter().len(), 10);
+        assert_eq!(h.iter().rev().count(), 10);
+        assert_eq!(h["h"], 7);
+
+        h.retain(|_, v| *v % 2 == 1);
+        let mut kept: Vec<_> = h.iter_sorted().map(|(k, v)| (*k, *v)).collect();
+        kept.sort();
+        assert_eq!(
+            kept,
+            [
+                ("Gato", 5),
+                ("Woffo", 11),
+                ("b", 1),
+                ("d", 3),
+                ("f", 5),
+                ("h", 7)
+            ]
+        );
+        assert_eq!(h.clone(), h);
+
+        let mut drained: Vec<_> = h.drain().collect();
+        drained.sort();
+        assert_eq!(drained, kept);
+        assert!(h.is_empty());
+        assert_eq!(h.stats().used_buckets, 0);
+    }
+
+    #[test]
+    fn vec_chaining() {
+        behaves_like_a_map::<Vec<HashItem<&str, i32>>>();
+    }
+
+    #[test]
+    fn linked_list_chaining() {
+        behaves_like_a_map::<LinkedList<HashItem<&str, i32>>>();
+    }
+
+    #[test]
+    fn linked_list_remove_from_the_middle() {
+        let mut list = LinkedList::new();
+        for i in 0..5 {
+            list.push(HashItem { k: i, v: i * 10 });
+        }
+        let removed = list.remove_where(|item| item.k == 2);
+        assert_eq!(removed.map(HashItem::into_parts), Some((2, 20)));
+        assert_eq!(list.remove_where(|item| item.k == 2).map(|_| ()), None);
+        let keys: Vec<_> = list.iter().map(|item| item.k).collect();
+        assert_eq!(keys, [0, 1, 3, 4]);
+    }
+}
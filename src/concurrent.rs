@@ -0,0 +1,174 @@
+// A table that can be shared between threads. Keys are split over a few
+// shards, each one a HashS2T behind its own RwLock: readers never block
+// each other, and writers only block the shard they touch.
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::sync::RwLock;
+
+use crate::{mix64, CharSumState, HashS2T};
+
+const DEFAULT_SHARDS: usize = 8;
+
+pub struct ConcurrentHashS2T<K, V, S = CharSumState> {
+    shards: Vec<RwLock<HashS2T<K, V, S>>>,
+    // same hasher the shards use for their buckets
+    hash_builder: S,
+}
+
+impl<K, V> ConcurrentHashS2T<K, V> {
+    pub fn new() -> Self {
+        Self::with_shards_and_hasher(DEFAULT_SHARDS, CharSumState::default())
+    }
+}
+
+impl<K, V> Default for ConcurrentHashS2T<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, S> ConcurrentHashS2T<K, V, S>
+where
+    S: Clone,
+{
+    pub fn with_shards_and_hasher(shards: usize, hash_builder: S) -> Self {
+        let shards = (0..shards.max(1))
+            .map(|_| RwLock::new(HashS2T::with_hasher(hash_builder.clone())))
+            .collect();
+        Self {
+            shards,
+            hash_builder,
+        }
+    }
+}
+
+impl<K, V, S> ConcurrentHashS2T<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    // The hash is mixed first: shards pick buckets from the plain hash,
+    // so choosing the shard from it too would leave most buckets unused
+    fn shard<Q>(&self, k: &Q) -> &RwLock<HashS2T<K, V, S>>
+    where
+        Q: Hash + ?Sized,
+    {
+        let hash = mix64(self.hash_builder.hash_one(k));
+        &self.shards[hash as usize % self.shards.len()]
+    }
+
+    pub fn insert(&self, k: K, v: V) -> Option<V> {
+        let mut shard = self.shard(&k).write().expect("shard lock poisoned");
+        shard.insert(k, v)
+    }
+
+    // Cloned out, so the shard isn't kept locked by the caller
+    pub fn get<Q>(&self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        let shard = self.shard(k).read().expect("shard lock poisoned");
+        shard.get(k).cloned()
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let shard = self.shard(k).read().expect("shard lock poisoned");
+        shard.contains_key(k)
+    }
+
+    pub fn remove<Q>(&self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut shard = self.shard(k).write().expect("shard lock poisoned");
+        shard.remove(k)
+    }
+
+    // Shards are counted one after the other, so with concurrent writers
+    // this is only a snapshot
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().expect("shard lock poisoned").len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn single_thread() {
+        let h = ConcurrentHashS2T::new();
+        assert_eq!(h.insert("Woffo", 1), None);
+        assert_eq!(h.insert("Gato", 2), None);
+        assert_eq!(h.insert("Gato", 3), Some(2));
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("Gato"), Some(3));
+        assert_eq!(h.remove("Woffo"), Some(1));
+        assert!(!h.contains_key("Woffo"));
+        assert_eq!(h.len(), 1);
+    }
+
+    #[test]
+    fn threads_insert_disjoint_ranges() {
+        let h = ConcurrentHashS2T::new();
+        thread::scope(|s| {
+            for t in 0..4 {
+                let h = &h;
+                s.spawn(move || {
+                    for i in t * 1000..(t + 1) * 1000 {
+                        h.insert(format!("key_{i}"), i);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(h.len(), 4000);
+        for i in 0..4000 {
+            assert_eq!(h.get(&format!("key_{i}")), Some(i));
+        }
+        // every shard got some of them
+        assert!(h
+            .shards
+            .iter()
+            .all(|shard| !shard.read().unwrap().is_empty()));
+    }
+
+    #[test]
+    fn concurrent_readers() {
+        let h = ConcurrentHashS2T::new();
+        for i in 0..100 {
+            h.insert(i, i * 2);
+        }
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for i in 0..100 {
+                        assert_eq!(h.get(&i), Some(i * 2));
+                    }
+                });
+            }
+            s.spawn(|| {
+                for i in 100..200 {
+                    h.insert(i, i * 2);
+                }
+            });
+        });
+        assert_eq!(h.len(), 200);
+    }
+}
@@ -0,0 +1,251 @@
+// A table whose entries can expire. insert_with_ttl stamps an entry with
+// the moment it stops being valid; from then on get treats it as missing
+// (and removes it while at it), and purge_expired sweeps all of them at
+// once. Time comes from a Clock, so tests can move it by hand.
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::time::{Duration, Instant};
+
+use crate::{CharSumState, HashS2T};
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+// So a clock can be shared with (or kept outside of) the table
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+pub struct ExpiringHashS2T<K, V, C = SystemClock, S = CharSumState> {
+    // key -> (when it expires, if ever, value)
+    table: HashS2T<K, (Option<Instant>, V), S>,
+    clock: C,
+}
+
+impl<K, V> ExpiringHashS2T<K, V> {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl<K, V> Default for ExpiringHashS2T<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, C> ExpiringHashS2T<K, V, C> {
+    pub fn with_clock(clock: C) -> Self {
+        Self::with_clock_and_hasher(clock, CharSumState::default())
+    }
+}
+
+impl<K, V, C, S> ExpiringHashS2T<K, V, C, S> {
+    pub fn with_clock_and_hasher(clock: C, hash_builder: S) -> Self {
+        Self {
+            table: HashS2T::with_hasher(hash_builder),
+            clock,
+        }
+    }
+
+    // Expired entries count until something removes them
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+impl<K, V, C, S> ExpiringHashS2T<K, V, C, S>
+where
+    K: Hash + Eq,
+    C: Clock,
+    S: BuildHasher,
+{
+    fn insert_until(&mut self, k: K, v: V, expiry: Option<Instant>) -> Option<V> {
+        let now = self.clock.now();
+        let (old_expiry, old) = self.table.insert(k, (expiry, v))?;
+        // an expired value was already gone as far as the caller knows
+        old_expiry.is_none_or(|t| t > now).then_some(old)
+    }
+
+    // Never expires; returns the previous value if it hadn't expired
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.insert_until(k, v, None)
+    }
+
+    // Expires ttl from now, overwriting whatever expiry the key had. A ttl
+    // too long for an Instant to hold (like Duration::MAX) never expires.
+    pub fn insert_with_ttl(&mut self, k: K, v: V, ttl: Duration) -> Option<V> {
+        let expiry = self.clock.now().checked_add(ttl);
+        self.insert_until(k, v, expiry)
+    }
+
+    // None for an expired entry, which is removed on the way
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = self.clock.now();
+        let (expiry, _) = self.table.get(k)?;
+        if expiry.is_some_and(|t| t <= now) {
+            self.table.remove(k);
+            return None;
+        }
+        self.table.get(k).map(|(_, v)| v)
+    }
+
+    // Like get, but leaves expired entries where they are (still None)
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = self.clock.now();
+        let (expiry, v) = self.table.get(k)?;
+        expiry.is_none_or(|t| t > now).then_some(v)
+    }
+
+    // Like get, but leaves expired entries where they are
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = self.clock.now();
+        self.table
+            .get(k)
+            .is_some_and(|(expiry, _)| expiry.is_none_or(|t| t > now))
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let now = self.clock.now();
+        let (expiry, v) = self.table.remove(k)?;
+        expiry.is_none_or(|t| t > now).then_some(v)
+    }
+
+    // Removes every expired entry
+    pub fn purge_expired(&mut self) {
+        let now = self.clock.now();
+        self.table
+            .retain(|_, (expiry, _)| expiry.is_none_or(|t| t > now));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+
+    use super::*;
+
+    // Stands still until advanced
+    struct FakeClock(Cell<Instant>);
+
+    impl FakeClock {
+        fn new() -> Self {
+            FakeClock(Cell::new(Instant::now()))
+        }
+
+        fn advance(&self, by: Duration) {
+            self.0.set(self.0.get() + by);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn expires_after_ttl() {
+        let clock = FakeClock::new();
+        let mut h = ExpiringHashS2T::with_clock(&clock);
+        h.insert_with_ttl("Woffo", 1, Duration::from_secs(10));
+        h.insert_with_ttl("Gato", 2, Duration::from_secs(60));
+        h.insert("Pez", 3);
+
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(h.get("Woffo"), Some(&1));
+
+        clock.advance(Duration::from_secs(1));
+        assert!(!h.contains_key("Woffo"));
+        assert_eq!(h.len(), 3);
+        // removed lazily, by the get
+        assert_eq!(h.get("Woffo"), None);
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("Gato"), Some(&2));
+
+        clock.advance(Duration::from_secs(3600));
+        assert!(!h.contains_key("Gato"));
+        h.purge_expired();
+        assert_eq!(h.len(), 1);
+        assert_eq!(h.get("Pez"), Some(&3));
+    }
+
+    #[test]
+    fn overwrite_resets_expiry() {
+        let clock = FakeClock::new();
+        let mut h = ExpiringHashS2T::with_clock(&clock);
+        h.insert_with_ttl("Woffo", 1, Duration::from_secs(10));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(
+            h.insert_with_ttl("Woffo", 2, Duration::from_secs(10)),
+            Some(1)
+        );
+        clock.advance(Duration::from_secs(8));
+        assert_eq!(h.get("Woffo"), Some(&2));
+
+        clock.advance(Duration::from_secs(2));
+        // expired values are never handed back
+        assert_eq!(h.insert("Woffo", 3), None);
+        assert_eq!(h.get("Woffo"), Some(&3));
+        h.insert_with_ttl("Gato", 4, Duration::ZERO);
+        assert_eq!(h.remove("Gato"), None);
+        assert_eq!(h.remove("Woffo"), Some(3));
+        assert!(h.is_empty());
+    }
+
+    #[test]
+    fn peek_leaves_expired() {
+        let clock = FakeClock::new();
+        let mut h = ExpiringHashS2T::with_clock(&clock);
+        h.insert_with_ttl("Woffo", 1, Duration::from_secs(10));
+        assert_eq!(h.peek("Woffo"), Some(&1));
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(h.peek("Woffo"), None);
+        assert_eq!(h.len(), 1);
+        assert_eq!(h.get("Woffo"), None);
+        assert_eq!(h.len(), 0);
+    }
+
+    #[test]
+    fn ttl_past_instant_never_expires() {
+        let clock = FakeClock::new();
+        let mut h = ExpiringHashS2T::with_clock(&clock);
+        assert_eq!(h.insert_with_ttl("Woffo", 1, Duration::MAX), None);
+        clock.advance(Duration::from_secs(100 * 365 * 24 * 3600));
+        h.purge_expired();
+        assert_eq!(h.get("Woffo"), Some(&1));
+        assert_eq!(h.insert_with_ttl("Woffo", 2, Duration::MAX), Some(1));
+    }
+}
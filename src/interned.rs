@@ -0,0 +1,183 @@
+// String keys stored once, however many tables use them. An Interner hands
+// out one Arc<str> per distinct key; tables built with the same interner
+// keep clones of that Arc instead of a String each. Keys stay interned
+// for as long as any clone of the interner is around, even once no table
+// uses them any more.
+use std::borrow::Borrow;
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Arc, Mutex};
+
+use crate::{CharSumState, HashS2T};
+
+// Cloning shares the keys: both clones intern into the same set
+#[derive(Clone, Default)]
+pub struct Interner {
+    keys: Arc<Mutex<HashS2T<Arc<str>, ()>>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The same Arc for every call with an equal k
+    pub fn intern(&self, k: &str) -> Arc<str> {
+        let mut keys = self.keys.lock().expect("interner lock poisoned");
+        if let Some((interned, _)) = keys.get_key_value(k) {
+            return Arc::clone(interned);
+        }
+        let interned: Arc<str> = Arc::from(k);
+        keys.insert(Arc::clone(&interned), ());
+        interned
+    }
+
+    // Distinct keys interned so far
+    pub fn len(&self) -> usize {
+        self.keys.lock().expect("interner lock poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub struct InternedHashS2T<V, S = CharSumState> {
+    table: HashS2T<Arc<str>, V, S>,
+    interner: Interner,
+}
+
+impl<V> InternedHashS2T<V> {
+    // With an interner of its own
+    pub fn new() -> Self {
+        Self::with_interner(Interner::new())
+    }
+
+    pub fn with_interner(interner: Interner) -> Self {
+        Self::with_interner_and_hasher(interner, CharSumState::default())
+    }
+}
+
+impl<V> Default for InternedHashS2T<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V, S> InternedHashS2T<V, S> {
+    pub fn with_interner_and_hasher(interner: Interner, hash_builder: S) -> Self {
+        Self {
+            table: HashS2T::with_hasher(hash_builder),
+            interner,
+        }
+    }
+
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Arc<str>, &V)> {
+        self.table.iter().map(|item| (item.key(), item.value()))
+    }
+}
+
+impl<V, S> InternedHashS2T<V, S>
+where
+    S: BuildHasher,
+{
+    // Only a key the table doesn't have yet goes through the interner
+    pub fn insert(&mut self, k: &str, v: V) -> Option<V> {
+        if let Some(old) = self.table.get_mut(k) {
+            return Some(std::mem::replace(old, v));
+        }
+        self.table.insert(self.interner.intern(k), v);
+        None
+    }
+
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        Arc<str>: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get(k)
+    }
+
+    // The key is the interned one, shared with every other table using
+    // the same interner
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&Arc<str>, &V)>
+    where
+        Arc<str>: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get_key_value(k)
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        Arc<str>: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.contains_key(k)
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        Arc<str>: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.remove(k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tables_share_keys() {
+        let interner = Interner::new();
+        let mut ages = InternedHashS2T::with_interner(interner.clone());
+        let mut owners = InternedHashS2T::with_interner(interner.clone());
+        ages.insert("Woffo", 3);
+        ages.insert("Gato", 5);
+        owners.insert(&String::from("Woffo"), "Rodrigo");
+
+        let (a, _) = ages.get_key_value("Woffo").unwrap();
+        let (b, _) = owners.get_key_value("Woffo").unwrap();
+        assert!(Arc::ptr_eq(a, b));
+        // the interner, and the two tables
+        assert_eq!(Arc::strong_count(a), 3);
+        assert_eq!(interner.len(), 2);
+
+        // overwriting doesn't intern again, and removing doesn't forget
+        assert_eq!(ages.insert("Woffo", 4), Some(3));
+        assert_eq!(ages.remove("Gato"), Some(5));
+        assert_eq!(ages.get("Woffo"), Some(&4));
+        assert!(!ages.contains_key("Gato"));
+        assert_eq!(interner.len(), 2);
+        assert!(Arc::ptr_eq(
+            &interner.intern("Gato"),
+            &interner.intern("Gato")
+        ));
+    }
+
+    #[test]
+    fn own_interner() {
+        let mut a = InternedHashS2T::new();
+        let mut b = InternedHashS2T::new();
+        a.insert("Woffo", 1);
+        b.insert("Woffo", 2);
+        let (ka, _) = a.get_key_value("Woffo").unwrap();
+        let (kb, _) = b.get_key_value("Woffo").unwrap();
+        assert!(!Arc::ptr_eq(ka, kb));
+        assert_eq!(a.interner().len(), 1);
+        assert_eq!(a.iter().collect::<Vec<_>>(), [(ka, &1)]);
+    }
+}
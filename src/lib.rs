@@ -0,0 +1,3673 @@
+//! A hash table written to learn how they work: every bucket holds a
+//! vector of the items hashed to it (*Closed Addressing*, see
+//! `CollisionStrategy` for other ways to hold them), and keys are hashed
+//! by summing their bytes unless another hasher is picked.
+//!
+//! ```
+//! use hash_table::HashS2T;
+//!
+//! let mut h = HashS2T::new();
+//! h.insert("Woffo", 1);
+//! h.insert("Gato", 2);
+//! assert_eq!(h.get("Gato"), Some(&2));
+//! assert_eq!(h.get("Pez"), None);
+//! ```
+//!
+//! Without the default `std` feature (and with `alloc`) the crate is
+//! `no_std`; `ConcurrentHashS2T`, `ExpiringHashS2T`, `InternedHashS2T`
+//! and the `HashMap` conversions need `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "alloc"))]
+compile_error!("the table needs a heap: enable the `alloc` feature (or `std`)");
+
+extern crate alloc;
+
+use alloc::collections::TryReserveError;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use core::iter::{FlatMap, Flatten};
+use core::marker::PhantomData;
+use core::ops::Index;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[cfg(feature = "ahash")]
+mod ahash_impl;
+mod bi;
+mod bytes;
+mod collision;
+#[cfg(feature = "std")]
+mod concurrent;
+#[cfg(feature = "std")]
+mod expiring;
+#[cfg(feature = "std")]
+mod interned;
+mod lru;
+mod memory;
+mod multi;
+mod normalized;
+mod open_addr;
+mod ordered;
+#[cfg(feature = "rand")]
+mod rand_impl;
+#[cfg(feature = "rayon")]
+mod rayon_impl;
+mod robin_hood;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod snapshot;
+
+pub use bi::BiHashS2T;
+pub use bytes::HashS2TBytes;
+pub use collision::CollisionStrategy;
+#[cfg(feature = "std")]
+pub use concurrent::ConcurrentHashS2T;
+#[cfg(feature = "std")]
+pub use expiring::{Clock, ExpiringHashS2T, SystemClock};
+#[cfg(feature = "std")]
+pub use interned::{InternedHashS2T, Interner};
+pub use lru::LruHashS2T;
+pub use memory::HeapSize;
+pub use multi::MultiHashS2T;
+#[cfg(feature = "unicode-normalization")]
+pub use normalized::Nfc;
+pub use normalized::{AsciiLowercase, KeyNormalizer, Lowercase, NormalizedHashS2T};
+pub use open_addr::OpenAddr;
+pub use ordered::OrderedHashS2T;
+pub use robin_hood::{ProbeStats, RobinHood};
+pub use snapshot::CowSnapshot;
+
+const DEFAULT_HASH2ST_SIZE: usize = 256;
+// items per bucket the table is sized for; going over it grows the table
+const DEFAULT_MAX_LOAD_FACTOR: f64 = 0.75;
+// how many times bigger the bucket vector gets on each grow
+const DEFAULT_GROWTH_FACTOR: usize = 2;
+
+const DEFAULT_HASH_MULTIPLIER: u64 = 31;
+
+// Still goes byte by byte, but multiplies by a prime before each add, so
+// anagrams and similar words no longer land together. u64 all the way
+// and wrapping: no overflow for long strings, and the same result on
+// 32-bit targets.
+#[derive(Default)]
+pub struct MulMixHasher(u64);
+
+impl Hasher for MulMixHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self
+                .0
+                .wrapping_mul(DEFAULT_HASH_MULTIPLIER)
+                .wrapping_add(u64::from(b));
+        }
+    }
+}
+
+// Select it with HashS2T::with_hasher(MulMixState::default())
+pub type MulMixState = BuildHasherDefault<MulMixHasher>;
+
+// The bucket out of len a table hashing with MulMixState puts s in
+pub fn mul_mix(s: &str, len: usize) -> usize {
+    (MulMixState::default().hash_one(s) % len as u64) as usize
+}
+
+// Legacy name for mul_mix. Not the default table's bucket: HashS2T<K, V>
+// hashes with CharSumState.
+pub fn default_hash(s: &str, len: usize) -> usize {
+    mul_mix(s, len)
+}
+
+// The plain byte sum default_hash started as, and still the table's
+// default hasher: HashS2T<K, V> is HashS2T<K, V, CharSumState>, and the
+// anagram collisions it causes are what most of the collision examples
+// here are built on. Hash with MulMixState for the better spread.
+#[derive(Default)]
+pub struct CharSumHasher(u64);
+
+impl Hasher for CharSumHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self.0.wrapping_add(b.into());
+        }
+    }
+}
+
+pub type CharSumState = BuildHasherDefault<CharSumHasher>;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// FNV-1a (64 bit): xor each byte in, then multiply by the FNV prime.
+// Every byte changes all the bits after it, so anagrams don't collide.
+// Goes through Fnv1aState, str terminator included, so it's the bucket
+// out of len a table hashing with it picks.
+pub fn fnv1a(s: &str, len: usize) -> usize {
+    (Fnv1aState::default().hash_one(s) % len as u64) as usize
+}
+
+pub struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+// Select it with HashS2T::with_hasher(Fnv1aState::default())
+pub type Fnv1aState = BuildHasherDefault<Fnv1aHasher>;
+
+const DJB2_START: u64 = 5381;
+
+// djb2 (Dan Bernstein): hash * 33 + c for each byte. Wrapping ops, so
+// long strings wrap around instead of overflowing. Goes through
+// Djb2State, so it's the bucket a table hashing with it picks.
+pub fn djb2(s: &str, len: usize) -> usize {
+    (Djb2State::default().hash_one(s) % len as u64) as usize
+}
+
+pub struct Djb2Hasher(u64);
+
+impl Default for Djb2Hasher {
+    fn default() -> Self {
+        Self(DJB2_START)
+    }
+}
+
+impl Hasher for Djb2Hasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self.0.wrapping_mul(33).wrapping_add(u64::from(b));
+        }
+    }
+}
+
+// Select it with HashS2T::with_hasher(Djb2State::default())
+pub type Djb2State = BuildHasherDefault<Djb2Hasher>;
+
+// splitmix64 finalizer: spreads every input bit over the whole output
+fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+// FNV-1a starting from a per-table seed, so which keys collide depends on
+// the seed and can't be worked out in advance from the hash function alone
+#[derive(Clone, Copy)]
+pub struct SeededState {
+    seed: u64,
+}
+
+pub struct SeededHasher(u64);
+
+impl BuildHasher for SeededState {
+    type Hasher = SeededHasher;
+
+    fn build_hasher(&self) -> SeededHasher {
+        SeededHasher(FNV_OFFSET_BASIS ^ mix64(self.seed))
+    }
+}
+
+impl Hasher for SeededHasher {
+    fn finish(&self) -> u64 {
+        mix64(self.0)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HashItem<K, V> {
+    k: K,
+    v: V,
+}
+
+// Read-only access to the key: changing it would leave the item in the
+// wrong bucket
+impl<K, V> HashItem<K, V> {
+    pub fn key(&self) -> &K {
+        &self.k
+    }
+
+    pub fn value(&self) -> &V {
+        &self.v
+    }
+
+    pub fn into_parts(self) -> (K, V) {
+        (self.k, self.v)
+    }
+}
+
+// Each hashnode holds the items hashed to it (a Vec unless another
+// CollisionStrategy is picked), since we are using Closed Addressing
+type HashNode<B> = Option<B>;
+
+// Fewest buckets that hold n items without going over the given load
+// factor
+fn min_buckets(n: usize, max_load_factor: f64) -> usize {
+    // f64::ceil is not in core
+    let min = n as f64 / max_load_factor;
+    // (the cast saturates, so past usize::MAX this stays there)
+    let buckets = min as usize;
+    if (buckets as f64) < min {
+        buckets.saturating_add(1)
+    } else {
+        buckets
+    }
+}
+
+// Enough buckets (a power of two, at least one) to hold n items
+// without going over the given load factor
+fn buckets_for(n: usize, max_load_factor: f64) -> usize {
+    min_buckets(n, max_load_factor).next_power_of_two()
+}
+
+// Smallest prime >= n; trial division is plenty for bucket counts
+fn next_prime(n: usize) -> usize {
+    let is_prime = |n: usize| {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    };
+    (n..)
+        .find(|&n| is_prime(n))
+        .expect("a prime above any bucket count")
+}
+
+fn empty_buckets<B>(buckets: usize) -> Vec<HashNode<B>> {
+    let mut items = Vec::with_capacity(buckets);
+    // vec![None; ...] requires Node: Clone
+    for _ in 0..buckets {
+        items.push(None);
+    }
+    items
+}
+
+// Snapshot of how items are spread over the buckets, to compare hashes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub entries: usize,
+    pub buckets: usize,
+    pub used_buckets: usize,
+    pub max_bucket_len: usize,
+    // over non-empty buckets only; 0.0 when there are none
+    pub avg_bucket_len: f64,
+    pub collisions: usize,
+}
+
+// What changed going from one table to another, see diff. Each group is
+// in the iteration order of the table its entries come from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff<'a, K, V> {
+    // only in the first table
+    pub removed: Vec<(&'a K, &'a V)>,
+    // only in the second one
+    pub added: Vec<(&'a K, &'a V)>,
+    // in both, with different values: (key, old value, new value)
+    pub changed: Vec<(&'a K, &'a V, &'a V)>,
+}
+
+impl<K, V> Diff<'_, K, V> {
+    // Both tables hold the same entries
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Clone)]
+pub struct HashS2T<K, V, S = CharSumState, B = Vec<HashItem<K, V>>> {
+    items: Vec<HashNode<B>>,
+    // number of stored items, kept up to date so len() is O(1)
+    len: usize,
+    // Items sharing their bucket with at least one other item, not
+    // counting the first one in each bucket; always len - used buckets.
+    // Overwrites don't change it, removals and resizes keep it in sync.
+    // After reset_stats it only counts the collisions since (until a
+    // resize counts them all again).
+    stat_collisions: usize,
+    // every bucket index is computed through it
+    hash_builder: S,
+    max_load_factor: f64,
+    growth_factor: usize,
+    // bucket counts are primes rather than powers of two, see
+    // with_prime_capacity
+    prime_buckets: bool,
+    // K and V only show up inside B. The PhantomData also keeps the auto
+    // traits honest: the table is Send (Sync) exactly when K, V, S and the
+    // buckets are, which the send_sync test checks.
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K, V, S, B> Default for HashS2T<K, V, S, B>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_buckets_and_hasher(DEFAULT_HASH2ST_SIZE, S::default())
+    }
+}
+
+impl<K, V> HashS2T<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(n: usize) -> Self {
+        Self::with_capacity_and_hasher(n, CharSumState::default())
+    }
+
+    // Default, grown up front if expected_entries wouldn't fit in its 256
+    // buckets at the default load factor: inserting that many never
+    // resizes. Unlike with_capacity, a small count doesn't make for a
+    // smaller table than default().
+    pub fn for_expected(expected_entries: usize) -> Self {
+        let buckets = buckets_for(expected_entries, DEFAULT_MAX_LOAD_FACTOR);
+        Self::with_buckets_and_hasher(buckets.max(DEFAULT_HASH2ST_SIZE), CharSumState::default())
+    }
+
+    // Like with_capacity, but the bucket count is the next prime instead
+    // of the next power of two, and stays prime when the table grows.
+    // Indexing is then a modulo, which uses every bit of the hash: a
+    // power of two only looks at the low ones, where weak hashes (like
+    // the byte sum) show their patterns.
+    pub fn with_prime_capacity(n: usize) -> Self {
+        let buckets = next_prime(min_buckets(n, DEFAULT_MAX_LOAD_FACTOR));
+        let mut h = Self::with_buckets_and_hasher(buckets, CharSumState::default());
+        h.prime_buckets = true;
+        h
+    }
+}
+
+impl<K, V> HashS2T<K, V, SeededState> {
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_hasher(SeededState { seed })
+    }
+}
+
+// Tables with another CollisionStrategy are built with default(), the
+// strategy can't be inferred from a hasher
+impl<K, V, S> HashS2T<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_buckets_and_hasher(DEFAULT_HASH2ST_SIZE, hash_builder)
+    }
+
+    pub fn with_capacity_and_hasher(n: usize, hash_builder: S) -> Self {
+        let buckets = buckets_for(n, DEFAULT_MAX_LOAD_FACTOR);
+        Self::with_buckets_and_hasher(buckets, hash_builder)
+    }
+}
+
+impl<K, V, S, B> HashS2T<K, V, S, B> {
+    // buckets must be a power of two, or a prime if prime_buckets gets
+    // set (or zero, for a table nothing gets inserted into), see index
+    fn with_buckets_and_hasher(buckets: usize, hash_builder: S) -> Self {
+        Self {
+            items: empty_buckets(buckets),
+            len: 0,
+            stat_collisions: 0,
+            hash_builder,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            growth_factor: DEFAULT_GROWTH_FACTOR,
+            prime_buckets: false,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Number of buckets, not of items (that's len)
+    pub fn capacity(&self) -> usize {
+        self.items.len()
+    }
+
+    // Items per bucket
+    pub fn load_factor(&self) -> f64 {
+        if self.items.is_empty() {
+            return 0.0;
+        }
+        self.len as f64 / self.items.len() as f64
+    }
+
+    // Items per bucket the next insert may take the table to before it
+    // grows. Lowering it below the current load doesn't resize right
+    // away, the next insert of a new key does. Panics unless f is in
+    // (0.0, 1.0].
+    pub fn set_max_load_factor(&mut self, f: f64) {
+        assert!(
+            f > 0.0 && f <= 1.0,
+            "max load factor must be in (0.0, 1.0], got {f}"
+        );
+        self.max_load_factor = f;
+    }
+
+    // How many times bigger the bucket vector gets when the table grows
+    // (rounded up to the next power of two, or prime). Panics if
+    // factor < 2, since a grow has to add buckets.
+    pub fn set_growth_factor(&mut self, factor: usize) {
+        assert!(
+            factor >= 2,
+            "growth factor must be at least 2, got {factor}"
+        );
+        self.growth_factor = factor;
+    }
+}
+
+impl<K, V, S, B> HashS2T<K, V, S, B>
+where
+    B: CollisionStrategy<K, V>,
+{
+    pub fn stats(&self) -> Stats {
+        let mut used_buckets = 0;
+        let mut max_bucket_len = 0;
+        for items in self.items.iter().flatten() {
+            used_buckets += 1;
+            max_bucket_len = max_bucket_len.max(items.len());
+        }
+        let avg_bucket_len = if used_buckets == 0 {
+            0.0
+        } else {
+            self.len as f64 / used_buckets as f64
+        };
+        Stats {
+            entries: self.len,
+            buckets: self.items.len(),
+            used_buckets,
+            max_bucket_len,
+            avg_bucket_len,
+            collisions: self.stat_collisions,
+        }
+    }
+
+    // Zeroes the collision count, for stats about what comes next only;
+    // the entries stay
+    pub fn reset_stats(&mut self) {
+        self.stat_collisions = 0;
+    }
+
+    // Items in each bucket, by bucket index; plotted, it shows how evenly
+    // the hasher spreads the keys
+    pub fn bucket_histogram(&self) -> Vec<usize> {
+        self.items
+            .iter()
+            .map(|node| node.as_ref().map_or(0, B::len))
+            .collect()
+    }
+
+    // Keeps the bucket vector (and its length) so hashing stays consistent
+    pub fn clear(&mut self) {
+        for node in &mut self.items {
+            *node = None;
+        }
+        self.len = 0;
+        self.stat_collisions = 0;
+    }
+
+    // Keeps only the items f returns true for; the bucket count stays
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        for node in &mut self.items {
+            if let Some(items) = node {
+                let before = items.len();
+                items.retain(|HashItem { k, v }| f(k, v));
+                self.len -= before - items.len();
+                // saturating: reset_stats may have dropped these already
+                self.stat_collisions = self
+                    .stat_collisions
+                    .saturating_sub(before - items.len().max(1));
+                if items.is_empty() {
+                    *node = None;
+                }
+            }
+        }
+    }
+
+    // The same keys, in the same buckets, with every value mapped through
+    // f; nothing gets rehashed. The new table chains with a Vec, whatever
+    // this one uses.
+    pub fn map_values<U, F>(&self, mut f: F) -> HashS2T<K, U, S>
+    where
+        K: Clone,
+        S: Clone,
+        F: FnMut(&V) -> U,
+    {
+        let mut h = HashS2T::with_buckets_and_hasher(self.items.len(), self.hash_builder.clone());
+        h.max_load_factor = self.max_load_factor;
+        h.growth_factor = self.growth_factor;
+        h.prime_buckets = self.prime_buckets;
+        for (i, node) in self.items.iter().enumerate() {
+            for HashItem { k, v } in node.iter().flat_map(B::iter) {
+                h.place(
+                    i,
+                    HashItem {
+                        k: k.clone(),
+                        v: f(v),
+                    },
+                );
+            }
+        }
+        h
+    }
+
+    // Like map_values, but the keys and values are moved out: nothing is
+    // cloned
+    pub fn into_map_values<U, F>(self, mut f: F) -> HashS2T<K, U, S>
+    where
+        F: FnMut(V) -> U,
+    {
+        let mut h = HashS2T::with_buckets_and_hasher(self.items.len(), self.hash_builder);
+        h.max_load_factor = self.max_load_factor;
+        h.growth_factor = self.growth_factor;
+        h.prime_buckets = self.prime_buckets;
+        for (i, node) in self.items.into_iter().enumerate() {
+            for HashItem { k, v } in node.into_iter().flatten() {
+                h.place(i, HashItem { k, v: f(v) });
+            }
+        }
+        h
+    }
+
+    // A copy holding only the entries f returns true for, in the same
+    // buckets; unlike retain, this table is left as it is
+    pub fn filter_into<F>(&self, mut f: F) -> Self
+    where
+        K: Clone,
+        V: Clone,
+        S: Clone,
+        F: FnMut(&K, &V) -> bool,
+    {
+        let mut h = Self::with_buckets_and_hasher(self.items.len(), self.hash_builder.clone());
+        h.max_load_factor = self.max_load_factor;
+        h.growth_factor = self.growth_factor;
+        h.prime_buckets = self.prime_buckets;
+        for (i, node) in self.items.iter().enumerate() {
+            for item in node.iter().flat_map(B::iter) {
+                if f(&item.k, &item.v) {
+                    h.place(i, item.clone());
+                }
+            }
+        }
+        h
+    }
+
+    // Frees the capacity removals left unused in the buckets and in the
+    // bucket vector, without moving any item: contents and bucket count
+    // stay the same (see shrink_to_fit for fewer buckets)
+    pub fn compact(&mut self) {
+        for items in self.items.iter_mut().flatten() {
+            items.shrink_to_fit();
+        }
+        self.items.shrink_to_fit();
+    }
+
+    // Takes out the entries f returns true for (f may change the values
+    // of the ones it keeps) and hands them back, bucket by bucket. Unlike
+    // std's, the work is done right away: the entries are gone even if
+    // the iterator is dropped unused.
+    pub fn extract_if<F>(&mut self, mut f: F) -> impl Iterator<Item = (K, V)>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let mut extracted = Vec::new();
+        for node in &mut self.items {
+            let Some(items) = node else { continue };
+            let before = items.len();
+            for mut item in core::mem::take(items) {
+                if f(&item.k, &mut item.v) {
+                    extracted.push((item.k, item.v));
+                } else {
+                    items.push(item);
+                }
+            }
+            self.len -= before - items.len();
+            self.stat_collisions = self
+                .stat_collisions
+                .saturating_sub(before - items.len().max(1));
+            if items.is_empty() {
+                *node = None;
+            }
+        }
+        extracted.into_iter()
+    }
+
+    // Moves every item out while keeping the bucket vector. The table is
+    // empty afterwards, even if the iterator is dropped half way
+    pub fn drain(&mut self) -> Drain<'_, K, V, B> {
+        Drain {
+            buckets: self.items.iter_mut(),
+            current: B::default().into_iter(),
+            len: &mut self.len,
+            stat_collisions: &mut self.stat_collisions,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn into_keys(self) -> impl Iterator<Item = K> {
+        self.into_iter().map(|HashItem { k, .. }| k)
+    }
+
+    pub fn into_values(self) -> impl Iterator<Item = V> {
+        self.into_iter().map(|HashItem { v, .. }| v)
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V, B> {
+        Iter {
+            inner: self.items.iter().flatten().flat_map(B::iter),
+            remaining: self.len,
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|HashItem { k, .. }| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|HashItem { v, .. }| v)
+    }
+
+    // Bucket order depends on the hasher and the bucket count; this one
+    // depends on neither
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (&K, &V)>
+    where
+        K: Ord,
+    {
+        let mut items: Vec<_> = self.iter().map(|HashItem { k, v }| (k, v)).collect();
+        items.sort_by(|a, b| a.0.cmp(b.0));
+        items.into_iter()
+    }
+
+    // iter_sorted under a name meant for snapshot tests
+    pub fn iter_deterministic(&self) -> impl Iterator<Item = (&K, &V)>
+    where
+        K: Ord,
+    {
+        self.iter_sorted()
+    }
+
+    // No index by value, so this scans every entry
+    pub fn contains_value(&self, v: &V) -> bool
+    where
+        V: PartialEq,
+    {
+        self.values().any(|x| x == v)
+    }
+
+    // Keys stay shared: changing one would leave it in the wrong bucket
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> {
+        self.items
+            .iter_mut()
+            .flatten()
+            .flat_map(B::iter_mut)
+            .map(|HashItem { k, v }| (&*k, v))
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.iter_mut().map(|(_, v)| v)
+    }
+
+    // For a power of two, masking the low bits gives the same bucket as
+    // the modulo, without the division
+    fn index(&self, hash: u64) -> usize {
+        let buckets = self.items.len();
+        if buckets.is_power_of_two() {
+            hash as usize & (buckets - 1)
+        } else {
+            (hash % buckets as u64) as usize
+        }
+    }
+
+    // Bucket count a resize to n buckets ends up with
+    fn round_buckets(&self, n: usize) -> usize {
+        if self.prime_buckets {
+            next_prime(n)
+        } else {
+            n.next_power_of_two()
+        }
+    }
+
+    // Places an item in bucket i, which must not hold its key already
+    fn place(&mut self, i: usize, item: HashItem<K, V>) -> &mut HashItem<K, V> {
+        self.len += 1;
+        let node = &mut self.items[i];
+        if node.is_some() {
+            self.stat_collisions += 1;
+        }
+        node.get_or_insert_with(B::default).push(item)
+    }
+}
+
+impl<K, V, S, B> HashS2T<K, V, S, B>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    B: CollisionStrategy<K, V>,
+{
+    fn hash<Q>(&self, k: &Q) -> u64
+    where
+        Q: Hash + ?Sized,
+    {
+        self.hash_builder.hash_one(k)
+    }
+
+    // Bucket a key (or anything it borrows as) belongs to; there must be
+    // at least one bucket
+    fn bucket<Q>(&self, k: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        self.index(self.hash(k))
+    }
+
+    // Places a new item (its key must not be in the table yet), growing
+    // the table first if the item would take it over the max load factor
+    fn push_item(&mut self, hash: u64, item: HashItem<K, V>) -> &mut HashItem<K, V> {
+        let buckets = self.items.len();
+        if (self.len + 1) as f64 > buckets as f64 * self.max_load_factor {
+            // one grow may not be enough after the max load factor was
+            // lowered
+            let needed = min_buckets(self.len + 1, self.max_load_factor);
+            self.resize((buckets.max(1) * self.growth_factor).max(needed));
+        }
+        let i = self.index(hash);
+        self.place(i, item)
+    }
+
+    // Rebuilds the bucket vector with the given number of buckets (rounded
+    // up to a power of two, or a prime), rehashing every item into it
+    fn resize(&mut self, buckets: usize) {
+        let buckets = self.round_buckets(buckets);
+        self.move_items(empty_buckets(buckets));
+    }
+
+    // Rehashes every item into the given (empty) bucket vector
+    fn move_items(&mut self, buckets: Vec<HashNode<B>>) {
+        let old = core::mem::replace(&mut self.items, buckets);
+        self.len = 0;
+        self.stat_collisions = 0;
+        for item in old.into_iter().flatten().flatten() {
+            let i = self.bucket(&item.k);
+            self.place(i, item);
+        }
+    }
+
+    // Grows the table (if needed) so `additional` more items fit without
+    // going over the max load factor
+    pub fn reserve(&mut self, additional: usize) {
+        let buckets = min_buckets(self.len + additional, self.max_load_factor);
+        if buckets > self.items.len() {
+            self.resize(buckets);
+        }
+    }
+
+    // Like reserve, but if the bucket vector can't be allocated the error
+    // comes back (and the table is left as it was) instead of aborting.
+    // Only the bucket vector is fallible: the buckets' own items still
+    // allocate as usual while they move over.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let min = min_buckets(self.len.saturating_add(additional), self.max_load_factor);
+        if min <= self.items.len() {
+            return Ok(());
+        }
+        let mut buckets = Vec::new();
+        // fails on sizes that could never be allocated before they get
+        // rounded up (a prime search up there would take forever)
+        buckets.try_reserve_exact(min)?;
+        let n = self.round_buckets(min);
+        buckets.try_reserve_exact(n)?;
+        buckets.resize_with(n, || None);
+        self.move_items(buckets);
+        Ok(())
+    }
+
+    // Shrinks the table to the fewest buckets (at least one) that hold
+    // the current items without going over the max load factor
+    pub fn shrink_to_fit(&mut self) {
+        let buckets = self.round_buckets(min_buckets(self.len, self.max_load_factor));
+        if buckets < self.items.len() {
+            self.resize(buckets);
+        }
+    }
+
+    // The same entries (and bucket count and settings) hashed with another
+    // hasher; only how they spread over the buckets changes. The hasher is
+    // part of the type, so this builds a new table rather than changing
+    // this one.
+    pub fn rehash<S2: BuildHasher>(self, hash_builder: S2) -> HashS2T<K, V, S2, B> {
+        let mut h = HashS2T::with_buckets_and_hasher(self.items.len(), hash_builder);
+        h.max_load_factor = self.max_load_factor;
+        h.growth_factor = self.growth_factor;
+        h.prime_buckets = self.prime_buckets;
+        for item in self.into_iter() {
+            let i = h.bucket(&item.k);
+            h.place(i, item);
+        }
+        h
+    }
+
+    // Inserts every pair, later ones overwriting earlier ones with the
+    // same key. The table grows once up front (from the size hint) rather
+    // than on the way.
+    pub fn insert_many<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        self.reserve(lower);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+
+    // Goes through entry, so the key is hashed once: the same hash finds
+    // the bucket to look in and, on a miss, the one to place it in
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        match self.entry(k) {
+            Entry::Occupied(mut entry) => Some(core::mem::replace(entry.get_mut(), v)),
+            Entry::Vacant(entry) => {
+                entry.insert(v);
+                None
+            }
+        }
+    }
+
+    // The bucket index is computed once and kept in the entry, so
+    // inserting into a vacant entry doesn't hash again
+    pub fn entry(&mut self, k: K) -> Entry<'_, K, V, S, B> {
+        if self.items.is_empty() {
+            self.resize(1);
+        }
+        let hash = self.hash(&k);
+        let i = self.index(hash);
+        let found = self.items[i]
+            .as_ref()
+            .is_some_and(|items| items.iter().any(|item| item.k == k));
+        // looked up again mutably: returning the first borrow from one
+        // branch only is something the borrow checker doesn't allow yet
+        if found {
+            let item = self.items[i]
+                .as_mut()
+                .and_then(|items| items.iter_mut().find(|item| item.k == k))
+                .expect("bucket holds the key");
+            return Entry::Occupied(OccupiedEntry { item });
+        }
+        Entry::Vacant(VacantEntry { h: self, hash, k })
+    }
+
+    pub fn try_insert(&mut self, k: K, v: V) -> Result<&mut V, OccupiedError<'_, K, V>> {
+        match self.entry(k) {
+            Entry::Occupied(entry) => Err(OccupiedError { entry, value: v }),
+            Entry::Vacant(entry) => Ok(entry.insert(v)),
+        }
+    }
+
+    // f only runs when the key is missing; the key is hashed once either way
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, k: K, f: F) -> &mut V {
+        self.entry(k).or_insert_with(f)
+    }
+
+    // Moves every entry of other into self. Keys only other has are
+    // inserted; for keys both have, on_conflict gets the current value to
+    // update and the incoming one
+    pub fn merge<S2, B2, F>(&mut self, other: HashS2T<K, V, S2, B2>, mut on_conflict: F)
+    where
+        B2: CollisionStrategy<K, V>,
+        F: FnMut(&K, &mut V, V),
+    {
+        self.reserve(other.len());
+        for HashItem { k, v } in other.into_iter() {
+            match self.entry(k) {
+                Entry::Occupied(entry) => on_conflict(&entry.item.k, &mut entry.item.v, v),
+                Entry::Vacant(entry) => {
+                    entry.insert(v);
+                }
+            }
+        }
+    }
+
+    // Compares against a newer version of the table: one lookup in other
+    // per entry of self, and one in self per entry of other
+    pub fn diff<'a, S2, B2>(&'a self, other: &'a HashS2T<K, V, S2, B2>) -> Diff<'a, K, V>
+    where
+        V: PartialEq,
+        S2: BuildHasher,
+        B2: CollisionStrategy<K, V>,
+    {
+        let mut diff = Diff {
+            removed: Vec::new(),
+            added: Vec::new(),
+            changed: Vec::new(),
+        };
+        for HashItem { k, v } in self.iter() {
+            match other.get(k) {
+                None => diff.removed.push((k, v)),
+                Some(new) if new != v => diff.changed.push((k, v, new)),
+                Some(_) => {}
+            }
+        }
+        for HashItem { k, v } in other.iter() {
+            if !self.contains_key(k) {
+                diff.added.push((k, v));
+            }
+        }
+        diff
+    }
+
+    fn get_item<Q>(&self, k: &Q) -> Option<&HashItem<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.items.is_empty() {
+            return None;
+        }
+        let i = self.bucket(k);
+        let node = &self.items[i];
+        node.as_ref()
+            .and_then(|items| items.iter().find(|item| item.k.borrow() == k))
+    }
+
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_item(k).map(|HashItem { v, .. }| v)
+    }
+
+    // Same as get. LruHashS2T and ExpiringHashS2T have one too, where
+    // unlike get it changes nothing: no recency update, no expiring
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get(k)
+    }
+
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_item(k).map(|HashItem { k, v }| (k, v))
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_item(k).is_some()
+    }
+
+    fn get_item_mut<Q>(&mut self, k: &Q) -> Option<&mut HashItem<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.items.is_empty() {
+            return None;
+        }
+        let i = self.bucket(k);
+        let node = &mut self.items[i];
+        node.as_mut()
+            .and_then(|items| items.iter_mut().find(|item| item.k.borrow() == k))
+    }
+
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_item_mut(k).map(|HashItem { v, .. }| v)
+    }
+
+    // Mutable references to the values of several keys at once, None for
+    // the missing ones. Panics if a key is asked for twice, since both
+    // references would point at the same value.
+    pub fn get_disjoint_mut<Q, const N: usize>(&mut self, ks: [&Q; N]) -> [Option<&mut V>; N]
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        for (i, k) in ks.iter().enumerate() {
+            assert!(!ks[..i].contains(k), "duplicate keys in get_disjoint_mut");
+        }
+        let mut values: [Option<&mut V>; N] = core::array::from_fn(|_| None);
+        if self.items.is_empty() {
+            return values;
+        }
+        let buckets = ks.map(|k| self.bucket(k));
+        // Only the buckets the keys hash to are searched. Visiting them in
+        // increasing order, each one split off what's left of the bucket
+        // vector, gives a mutable borrow of every one of them at once.
+        let mut order: [usize; N] = core::array::from_fn(|j| j);
+        order.sort_unstable_by_key(|&j| buckets[j]);
+        let mut rest: &mut [HashNode<B>] = &mut self.items;
+        // index in self.items of rest[0]
+        let mut offset = 0;
+        for (n, &j) in order.iter().enumerate() {
+            let b = buckets[j];
+            if n > 0 && buckets[order[n - 1]] == b {
+                // keys sharing a bucket were all looked for already
+                continue;
+            }
+            let (node, tail) = core::mem::take(&mut rest)[b - offset..]
+                .split_first_mut()
+                .expect("bucket indices are in range");
+            rest = tail;
+            offset = b + 1;
+            for item in node.iter_mut().flat_map(B::iter_mut) {
+                let k = item.k.borrow();
+                if let Some(j) = (0..N).find(|&j| buckets[j] == b && ks[j] == k) {
+                    values[j] = Some(&mut item.v);
+                }
+            }
+        }
+        values
+    }
+
+    // Swaps the values of a and b if both are there (true), leaving the
+    // table as it was otherwise (false). Swapping a key with itself does
+    // nothing, and is true if the key is there.
+    pub fn swap<Q>(&mut self, a: &Q, b: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if a == b {
+            return self.contains_key(a);
+        }
+        match self.get_disjoint_mut([a, b]) {
+            [Some(a), Some(b)] => {
+                core::mem::swap(a, b);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Moves the value of from to the key to (into to's bucket, replacing
+    // its value if it had one). False, and nothing changes, if from is not
+    // there.
+    pub fn rename_key<Q>(&mut self, from: &Q, to: K) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let Some(v) = self.remove(from) else {
+            return false;
+        };
+        self.insert(to, v);
+        true
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    // Also hands back the key as stored, which may not be the one passed
+    // (only equal to it)
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.items.is_empty() {
+            return None;
+        }
+        let i = self.bucket(k);
+        let node = &mut self.items[i];
+        let items = node.as_mut()?;
+        let HashItem { k, v } = items.remove_where(|item| item.k.borrow() == k)?;
+        if items.is_empty() {
+            *node = None;
+        } else {
+            self.stat_collisions = self.stat_collisions.saturating_sub(1);
+        }
+        self.len -= 1;
+        Some((k, v))
+    }
+}
+
+pub enum Entry<'a, K, V, S, B = Vec<HashItem<K, V>>> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V, S, B>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    item: &'a mut HashItem<K, V>,
+}
+
+// Returned by try_insert when the key is already there: the table is left
+// as it was and the rejected value is handed back
+pub struct OccupiedError<'a, K, V> {
+    pub entry: OccupiedEntry<'a, K, V>,
+    pub value: V,
+}
+
+impl<K, V> fmt::Debug for OccupiedError<'_, K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedError")
+            .field("key", &self.entry.item.k)
+            .field("old_value", &self.entry.item.v)
+            .field("new_value", &self.value)
+            .finish()
+    }
+}
+
+impl<K, V> fmt::Display for OccupiedError<'_, K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to insert {:?}, key {:?} already exists with value {:?}",
+            self.value, self.entry.item.k, self.entry.item.v,
+        )
+    }
+}
+
+impl<K, V> core::error::Error for OccupiedError<'_, K, V>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+}
+
+pub struct VacantEntry<'a, K, V, S, B = Vec<HashItem<K, V>>> {
+    h: &'a mut HashS2T<K, V, S, B>,
+    // kept so inserting doesn't hash again, even if the table grows
+    hash: u64,
+    k: K,
+}
+
+impl<K, V, S, B> Entry<'_, K, V, S, B> {
+    // The key entry() was called with; a vacant entry still owns it, an
+    // occupied one hands back the equal key already in the table
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+}
+
+impl<'a, K, V, S, B> Entry<'a, K, V, S, B>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    B: CollisionStrategy<K, V>,
+{
+    pub fn or_insert(self, v: V) -> &'a mut V {
+        self.or_insert_with(|| v)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, f: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.item.k
+    }
+
+    pub fn get(&self) -> &V {
+        &self.item.v
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.item.v
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.item.v
+    }
+}
+
+impl<K, V, S, B> VacantEntry<'_, K, V, S, B> {
+    pub fn key(&self) -> &K {
+        &self.k
+    }
+
+    // Gives the key back without inserting anything
+    pub fn into_key(self) -> K {
+        self.k
+    }
+}
+
+impl<'a, K, V, S, B> VacantEntry<'a, K, V, S, B>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    B: CollisionStrategy<K, V>,
+{
+    pub fn insert(self, v: V) -> &'a mut V {
+        let item = HashItem { k: self.k, v };
+        &mut self.h.push_item(self.hash, item).v
+    }
+}
+
+// Map-style, like HashMap: buckets are an implementation detail
+impl<K, V, S, B> fmt::Debug for HashS2T<K, V, S, B>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+    B: CollisionStrategy<K, V>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|HashItem { k, v }| (k, v)))
+            .finish()
+    }
+}
+
+// One line, {a: 1, b: 2}, sorted by key so the output doesn't depend on
+// the bucket layout
+impl<K, V, S, B> fmt::Display for HashS2T<K, V, S, B>
+where
+    K: fmt::Display + Ord,
+    V: fmt::Display,
+    B: CollisionStrategy<K, V>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("{")?;
+        for (i, (k, v)) in self.iter_sorted().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{k}: {v}")?;
+        }
+        f.write_str("}")
+    }
+}
+
+// Same entries means equal, whatever the bucket layout, insertion
+// order or collision history
+impl<K, V, S, B> PartialEq for HashS2T<K, V, S, B>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    S: BuildHasher,
+    B: CollisionStrategy<K, V>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .iter()
+                .all(|HashItem { k, v }| other.get(k).is_some_and(|other_v| v == other_v))
+    }
+}
+
+impl<K, V, S, B> Eq for HashS2T<K, V, S, B>
+where
+    K: Hash + Eq,
+    V: Eq,
+    S: BuildHasher,
+    B: CollisionStrategy<K, V>,
+{
+}
+
+impl<K, Q, V, S, B> Index<&Q> for HashS2T<K, V, S, B>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+    B: CollisionStrategy<K, V>,
+{
+    type Output = V;
+
+    // Panics if the key is not in the table
+    fn index(&self, k: &Q) -> &V {
+        self.get(k).expect("no entry found for key")
+    }
+}
+
+pub struct Drain<'a, K, V, B: CollisionStrategy<K, V> = Vec<HashItem<K, V>>> {
+    buckets: core::slice::IterMut<'a, HashNode<B>>,
+    // items taken out of the last visited bucket
+    current: B::IntoIter,
+    // The table's counters, brought down a bucket at a time, so they
+    // still match what's left if the Drain is leaked (mem::forget) rather
+    // than dropped
+    len: &'a mut usize,
+    stat_collisions: &'a mut usize,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K, V, B: CollisionStrategy<K, V>> Iterator for Drain<'_, K, V, B> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(HashItem { k, v }) = self.current.next() {
+                return Some((k, v));
+            }
+            let node = self.buckets.next()?;
+            if let Some(items) = node.take() {
+                *self.len -= items.len();
+                let collided = items.len().saturating_sub(1);
+                *self.stat_collisions = self.stat_collisions.saturating_sub(collided);
+                self.current = items.into_iter();
+            }
+        }
+    }
+}
+
+impl<K, V, B: CollisionStrategy<K, V>> Drop for Drain<'_, K, V, B> {
+    fn drop(&mut self) {
+        for node in &mut self.buckets {
+            *node = None;
+        }
+        *self.len = 0;
+        *self.stat_collisions = 0;
+    }
+}
+
+// Every item of every non-empty bucket, in order
+type BucketItems<'a, K, V, B> = FlatMap<
+    Flatten<core::slice::Iter<'a, HashNode<B>>>,
+    <B as CollisionStrategy<K, V>>::Iter<'a>,
+    fn(&'a B) -> <B as CollisionStrategy<K, V>>::Iter<'a>,
+>;
+
+// Buckets in order, then the items in each one (backwards: the last
+// bucket first, and its items last to first). The inner flatten goes
+// through the Option of every bucket, the flat_map through its items.
+pub struct Iter<'a, K, V, B = Vec<HashItem<K, V>>>
+where
+    K: 'a,
+    V: 'a,
+    B: CollisionStrategy<K, V> + 'a,
+{
+    inner: BucketItems<'a, K, V, B>,
+    // from the table's len, so len() doesn't have to walk the buckets
+    remaining: usize,
+}
+
+impl<'a, K: 'a, V: 'a, B: CollisionStrategy<K, V>> Iterator for Iter<'a, K, V, B> {
+    type Item = &'a HashItem<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, B: CollisionStrategy<K, V>> DoubleEndedIterator for Iter<'_, K, V, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<K, V, B: CollisionStrategy<K, V>> ExactSizeIterator for Iter<'_, K, V, B> {}
+
+pub struct IntoIter<K, V, B: CollisionStrategy<K, V> = Vec<HashItem<K, V>>> {
+    inner: Flatten<Flatten<alloc::vec::IntoIter<HashNode<B>>>>,
+    remaining: usize,
+    marker: PhantomData<(K, V)>,
+}
+
+impl<K, V, B: CollisionStrategy<K, V>> Iterator for IntoIter<K, V, B> {
+    type Item = HashItem<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, B: CollisionStrategy<K, V>> DoubleEndedIterator for IntoIter<K, V, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next_back()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+}
+
+impl<K, V, B: CollisionStrategy<K, V>> ExactSizeIterator for IntoIter<K, V, B> {}
+
+// Later pairs overwrite earlier ones with the same key, like insert
+impl<K, V, S, B> FromIterator<(K, V)> for HashS2T<K, V, S, B>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+    B: CollisionStrategy<K, V>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        let buckets = buckets_for(lower, DEFAULT_MAX_LOAD_FACTOR);
+        let mut h = Self::with_buckets_and_hasher(buckets, S::default());
+        for (k, v) in iter {
+            h.insert(k, v);
+        }
+        h
+    }
+}
+
+impl<'a, V, S, B> FromIterator<(&'a str, V)> for HashS2T<String, V, S, B>
+where
+    S: BuildHasher + Default,
+    B: CollisionStrategy<String, V>,
+{
+    fn from_iter<I: IntoIterator<Item = (&'a str, V)>>(iter: I) -> Self {
+        iter.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+}
+
+impl<K, V, S, B> Extend<(K, V)> for HashS2T<K, V, S, B>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+    B: CollisionStrategy<K, V>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        self.insert_many(iter);
+    }
+}
+
+impl<'a, V, S, B> Extend<(&'a str, V)> for HashS2T<String, V, S, B>
+where
+    S: BuildHasher,
+    B: CollisionStrategy<String, V>,
+{
+    fn extend<I: IntoIterator<Item = (&'a str, V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(k, v)| (k.to_string(), v)));
+    }
+}
+
+// Items are moved over, keys and values are never cloned
+#[cfg(feature = "std")]
+impl<K, V, S, S2, B> From<HashS2T<K, V, S, B>> for HashMap<K, V, S2>
+where
+    K: Hash + Eq,
+    S2: BuildHasher + Default,
+    B: CollisionStrategy<K, V>,
+{
+    fn from(h: HashS2T<K, V, S, B>) -> Self {
+        let mut map = HashMap::with_capacity_and_hasher(h.len(), S2::default());
+        map.extend(h.into_iter().map(|HashItem { k, v }| (k, v)));
+        map
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S, S2, B> From<HashMap<K, V, S2>> for HashS2T<K, V, S, B>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+    B: CollisionStrategy<K, V>,
+{
+    fn from(map: HashMap<K, V, S2>) -> Self {
+        let buckets = buckets_for(map.len(), DEFAULT_MAX_LOAD_FACTOR);
+        let mut h = Self::with_buckets_and_hasher(buckets, S::default());
+        for (k, v) in map {
+            h.insert(k, v);
+        }
+        h
+    }
+}
+
+impl<K, V, S, B: CollisionStrategy<K, V>> IntoIterator for HashS2T<K, V, S, B> {
+    type Item = HashItem<K, V>;
+    type IntoIter = IntoIter<K, V, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            remaining: self.len,
+            inner: self.items.into_iter().flatten().flatten(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, S, B: CollisionStrategy<K, V>> IntoIterator for &'a HashS2T<K, V, S, B> {
+    type Item = &'a HashItem<K, V>;
+    type IntoIter = Iter<'a, K, V, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// hashs2t! { "a" => 1, "b" => 2 } builds a table sized for its pairs
+#[macro_export]
+macro_rules! hashs2t {
+    (@unit $k:expr) => {
+        ()
+    };
+    () => {
+        $crate::HashS2T::new()
+    };
+    ($($k:expr => $v:expr),+ $(,)?) => {{
+        let n = <[()]>::len(&[$($crate::hashs2t!(@unit $k)),+]);
+        let mut h = $crate::HashS2T::with_capacity(n);
+        $(
+            h.insert($k, $v);
+        )+
+        h
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow::Cow;
+    use std::cell::Cell;
+    use std::collections::hash_map::RandomState;
+    use std::collections::LinkedList;
+    use std::fmt::Debug;
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn words() -> impl Iterator<Item = &'static str> {
+        include_str!("../testdata/words.txt").split_whitespace()
+    }
+
+    fn expected_items<K, V>(h: &HashS2T<K, V>, expected: &[(K, V)])
+    where
+        K: Clone + PartialEq + Debug,
+        V: PartialOrd + Clone + Debug,
+    {
+        // values must exist
+        let mut items: Vec<_> = h.iter().collect();
+        items.sort_by(|HashItem { v: v1, .. }, HashItem { v: v2, .. }| v1.partial_cmp(v2).unwrap());
+        assert_eq!(
+            items
+                .iter()
+                .map(|HashItem { k, v }| (k.clone(), v.clone()))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn insert() {
+        let mut h = HashS2T::new();
+        assert_eq!(h.insert("a", 1), None);
+        assert_eq!(h.insert("b", 2), None);
+        assert_eq!(h.insert("c", 2), None);
+        // overwriting hands back the old value
+        assert_eq!(h.insert("c", 3), Some(2));
+
+        assert_eq!(h.items.len(), DEFAULT_HASH2ST_SIZE);
+        assert_eq!(h.len(), 3);
+
+        // values must exist
+        expected_items(&h, &[("a", 1), ("b", 2), ("c", 3)]);
+    }
+
+    // Char-sum hashing that counts how many hashes were computed
+    #[derive(Clone, Default)]
+    struct CountingState(Rc<Cell<usize>>);
+
+    impl BuildHasher for CountingState {
+        type Hasher = CharSumHasher;
+
+        fn build_hasher(&self) -> CharSumHasher {
+            self.0.set(self.0.get() + 1);
+            CharSumHasher::default()
+        }
+    }
+
+    #[test]
+    fn insert_hashes_once() {
+        let state = CountingState::default();
+        let hashes = state.0.clone();
+        let mut h = HashS2T::with_hasher(state);
+
+        // miss, into an empty bucket and into a used one
+        h.insert("ab", 1);
+        assert_eq!(hashes.replace(0), 1);
+        h.insert("ba", 2);
+        assert_eq!(hashes.replace(0), 1);
+        // hit
+        h.insert("ab", 3);
+        assert_eq!(hashes.replace(0), 1);
+
+        // growing rehashes what was already there, and nothing else
+        let mut h = HashS2T::with_capacity_and_hasher(3, CountingState::default());
+        let hashes = h.hash_builder.0.clone();
+        for k in ["a", "b", "c"] {
+            h.insert(k, 0);
+        }
+        assert_eq!(h.items.len(), 4);
+        assert_eq!(hashes.replace(0), 3);
+        h.insert("d", 0);
+        assert_eq!(h.items.len(), 8);
+        assert_eq!(hashes.replace(0), 1 + 3);
+        assert_eq!(h.len(), 4);
+        assert!(["a", "b", "c", "d"].iter().all(|k| h.contains_key(k)));
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut h = HashS2T::with_capacity(0);
+        assert_eq!(h.items.len(), 1);
+        // a single bucket is still a working table
+        h.insert("a", 1);
+        h.insert("b", 2);
+        expected_items(&h, &[("a", 1), ("b", 2)]);
+
+        let h: HashS2T<&str, ()> = HashS2T::with_capacity(3);
+        assert_eq!(h.items.len(), 4);
+
+        let h: HashS2T<&str, ()> = HashS2T::with_capacity(100_000);
+        assert_eq!(h.items.len(), 1 << 18);
+        assert!(h.items.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn for_expected() {
+        for expected in [0, 10, 192, 193, 1000, 5000] {
+            let mut h = HashS2T::for_expected(expected);
+            let buckets = h.items.len();
+            assert!(buckets >= DEFAULT_HASH2ST_SIZE);
+            for i in 0..expected {
+                h.insert(i, i);
+            }
+            assert_eq!(h.items.len(), buckets, "resized before {expected}");
+        }
+        // 192 is as many as 256 buckets take, and the next one grows them
+        let mut h = HashS2T::for_expected(192);
+        h.extend((0..192).map(|i| (i, i)));
+        assert_eq!(h.items.len(), 256);
+        h.insert(192, 192);
+        assert_eq!(h.items.len(), 512);
+    }
+
+    #[test]
+    fn stress() {
+        let mut h = HashS2T::new();
+        for key_i in 0..5000 {
+            let key = format!("key_{key_i}");
+            let val = key_i + 42;
+            h.insert(key.clone(), val);
+            // insert twice
+            h.insert(key.clone(), val);
+            assert_eq!(*h.get(&key).unwrap(), val)
+        }
+        assert_eq!(h.len(), 5000);
+    }
+
+    #[test]
+    fn resize() {
+        let mut h = HashS2T::new();
+        for key_i in 0..10_000 {
+            h.insert(format!("key_{key_i}"), key_i);
+        }
+        assert_eq!(h.len(), 10_000);
+        assert!(h.items.len() > DEFAULT_HASH2ST_SIZE);
+        assert!(h.len as f64 <= h.items.len() as f64 * DEFAULT_MAX_LOAD_FACTOR);
+
+        // scattered lookups, still in the table after every grow
+        let mut key_i: usize = 1;
+        for _ in 0..1000 {
+            key_i = (key_i * 7919 + 13) % 10_000;
+            assert_eq!(h.get(&format!("key_{key_i}")), Some(&key_i));
+        }
+        assert_eq!(h.get("key_10000"), None);
+    }
+
+    #[test]
+    fn set_max_load_factor() {
+        let grows_at = |f: f64| {
+            let mut h = HashS2T::with_capacity(6);
+            h.set_max_load_factor(f);
+            let buckets = h.capacity();
+            (0..).find(|&i| {
+                h.insert(i, i);
+                h.capacity() > buckets
+            })
+        };
+        // 8 buckets: over 6 items at 0.75, over 2 at 0.25, over 8 at 1.0
+        assert_eq!(grows_at(0.75), Some(6));
+        assert_eq!(grows_at(0.25), Some(2));
+        assert_eq!(grows_at(1.0), Some(8));
+
+        let mut h = HashS2T::with_capacity(6);
+        for i in 0..6 {
+            h.insert(i, i);
+        }
+        h.set_max_load_factor(0.1);
+        // already over it, but nothing happens until the next insert
+        assert_eq!(h.capacity(), 8);
+        h.insert(0, 10);
+        assert_eq!(h.capacity(), 8);
+        h.insert(6, 6);
+        // far enough that doubling alone wouldn't do: 7 / 0.1 is 70
+        assert_eq!(h.capacity(), 128);
+        assert!(h.load_factor() <= 0.1);
+    }
+
+    #[test]
+    #[should_panic(expected = "max load factor must be in (0.0, 1.0], got 1.5")]
+    fn set_max_load_factor_too_big() {
+        HashS2T::<u8, u8>::new().set_max_load_factor(1.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "max load factor must be in (0.0, 1.0], got 0")]
+    fn set_max_load_factor_zero() {
+        HashS2T::<u8, u8>::new().set_max_load_factor(0.0);
+    }
+
+    #[test]
+    fn set_growth_factor() {
+        let mut h = HashS2T::with_capacity(3);
+        h.set_growth_factor(4);
+        for i in 0..4 {
+            h.insert(i, i);
+        }
+        assert_eq!(h.capacity(), 16);
+        for i in 4..13 {
+            h.insert(i, i);
+        }
+        assert_eq!(h.capacity(), 64);
+
+        let mut h = HashS2T::with_prime_capacity(3);
+        assert_eq!(h.capacity(), 5);
+        h.set_growth_factor(3);
+        for i in 0..4 {
+            h.insert(i, i);
+        }
+        // 15 buckets, rounded up to a prime
+        assert_eq!(h.capacity(), 17);
+    }
+
+    #[test]
+    #[should_panic(expected = "growth factor must be at least 2, got 1")]
+    fn set_growth_factor_one() {
+        HashS2T::<u8, u8>::new().set_growth_factor(1);
+    }
+
+    #[test]
+    fn resize_settings() {
+        let mut h = HashS2T::with_capacity(3);
+        assert_eq!(h.items.len(), 4);
+        h.max_load_factor = 0.5;
+        h.growth_factor = 4;
+
+        h.insert("a", 1);
+        h.insert("b", 2);
+        assert_eq!(h.items.len(), 4);
+        // the third item goes over half a bucket per item
+        h.insert("c", 3);
+        assert_eq!(h.items.len(), 16);
+        expected_items(&h, &[("a", 1), ("b", 2), ("c", 3)]);
+    }
+
+    #[test]
+    fn masked_index() {
+        for buckets in [1, 2, 8, 256, 1 << 20] {
+            let h: HashS2T<&str, ()> =
+                HashS2T::with_buckets_and_hasher(buckets, CharSumState::default());
+            let hashes = (0..10_000u64).chain([u64::MAX, u64::MAX - 1, 1 << 40, (1 << 40) + 3]);
+            for hash in hashes {
+                assert_eq!(h.index(hash), (hash % buckets as u64) as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn prime_capacity() {
+        let is_prime = |n: usize| n >= 2 && (2..n).all(|d| !n.is_multiple_of(d));
+        let mut h = HashS2T::with_prime_capacity(100);
+        // 100 / 0.75 is 133.3, and the next prime after 134 is 137
+        assert_eq!(h.capacity(), 137);
+        for hash in [0, 136, 137, 1000, u64::MAX] {
+            assert_eq!(h.index(hash), (hash % 137) as usize);
+        }
+
+        for i in 0..103 {
+            h.insert(i, i);
+        }
+        // grown past 137 * 0.75 items: double, then the next prime
+        assert_eq!(h.capacity(), 277);
+        for i in 103..5000 {
+            h.insert(i, i);
+        }
+        assert!(is_prime(h.capacity()));
+        h.reserve(10_000);
+        assert!(is_prime(h.capacity()));
+        for i in 0..5000 {
+            assert_eq!(h.get(&i), Some(&i));
+        }
+
+        h.retain(|k, _| *k < 10);
+        h.shrink_to_fit();
+        // 10 / 0.75 rounds up to 14
+        assert_eq!(h.capacity(), 17);
+        assert_eq!(h.len(), 10);
+        assert_eq!(next_prime(0), 2);
+        assert_eq!(HashS2T::<u8, u8>::with_prime_capacity(0).capacity(), 2);
+    }
+
+    #[test]
+    fn resize_rounds_to_power_of_two() {
+        let mut h = HashS2T::with_capacity(3);
+        h.growth_factor = 3;
+        for i in 0..4 {
+            h.insert(i, i);
+        }
+        // 4 * 3 buckets, rounded up
+        assert_eq!(h.items.len(), 16);
+
+        h.resize(100);
+        assert_eq!(h.items.len(), 128);
+        for i in 0..4 {
+            assert_eq!(h.get(&i), Some(&i));
+        }
+    }
+
+    #[test]
+    fn item_accessors() {
+        let mut h: HashS2T<String, Vec<i32>> = HashS2T::new();
+        h.insert("Woffo".to_string(), vec![1, 2]);
+
+        let item = h.iter().next().unwrap();
+        assert_eq!(item.key(), "Woffo");
+        assert_eq!(item.value(), &[1, 2]);
+
+        let (k, v) = h.into_iter().next().unwrap().into_parts();
+        assert_eq!(k, "Woffo");
+        assert_eq!(v, [1, 2]);
+    }
+
+    #[test]
+    fn named_iterators() {
+        fn is_iterator<I: Iterator>(_: &I) {}
+
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        for (i, k) in ["ab", "ba", "Woffo", "Gato"].into_iter().enumerate() {
+            h.insert(k.to_string(), i as i32);
+        }
+
+        let iter: Iter<'_, String, i32> = h.iter();
+        is_iterator(&iter);
+        let mut seen: Vec<_> = iter
+            .map(|item| (item.key().as_str(), *item.value()))
+            .collect();
+        seen.sort();
+        assert_eq!(seen, [("Gato", 3), ("Woffo", 2), ("ab", 0), ("ba", 1)]);
+        assert_eq!((&h).into_iter().count(), 4);
+
+        // no 'static needed anymore: the keys borrow from a local
+        let local = String::from("local");
+        let mut borrowed = HashS2T::new();
+        borrowed.insert(local.as_str(), 1);
+        let into_iter: IntoIter<&str, i32> = borrowed.into_iter();
+        is_iterator(&into_iter);
+        assert_eq!(
+            into_iter.map(HashItem::into_parts).collect::<Vec<_>>(),
+            [("local", 1)]
+        );
+    }
+
+    #[test]
+    fn iter_len() {
+        let mut h = HashS2T::new();
+        assert_eq!(h.iter().len(), 0);
+        for (v, k) in words().enumerate() {
+            h.insert(k, v);
+        }
+        let mut iter = h.iter();
+        assert_eq!(iter.len(), h.len());
+        assert_eq!(iter.len(), h.iter().count());
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.len(), h.len() - 2);
+        assert_eq!(iter.count(), h.len() - 2);
+        assert_eq!(h.clone().into_iter().len(), h.len());
+    }
+
+    #[test]
+    fn iter_rev() {
+        let mut h = HashS2T::new();
+        // collisions too, to check the order inside a bucket
+        for (v, k) in ["abc", "bca", "cab", "a", "b", "Woffo"]
+            .into_iter()
+            .enumerate()
+        {
+            h.insert(k, v);
+        }
+        let forward: Vec<_> = h.iter().map(HashItem::key).collect();
+        let mut backward: Vec<_> = h.iter().rev().map(HashItem::key).collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        // from both ends at once, meeting in the middle
+        let mut iter = h.iter();
+        let mut both = Vec::new();
+        while let (Some(front), back) = (iter.next(), iter.next_back()) {
+            both.push(front.key());
+            both.extend(back.map(HashItem::key));
+        }
+        assert_eq!(both.len(), h.len());
+
+        let mut owned: Vec<_> = h.clone().into_iter().rev().map(|item| item.k).collect();
+        owned.reverse();
+        assert_eq!(owned.iter().collect::<Vec<_>>(), forward);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        h.insert("b", 2);
+        h.insert("ab", 3);
+        h.insert("ba", 4);
+
+        for (_, v) in h.iter_mut() {
+            *v *= 2;
+        }
+        expected_items(&h, &[("a", 2), ("b", 4), ("ab", 6), ("ba", 8)]);
+    }
+
+    #[test]
+    fn iter_sorted() {
+        let mut h = HashS2T::new();
+        for (k, v) in [("pez", 1), ("Woffo", 2), ("ba", 3), ("Gato", 4), ("ab", 5)] {
+            h.insert(k, v);
+        }
+        let sorted: Vec<_> = h.iter_sorted().collect();
+        assert_eq!(
+            sorted,
+            [
+                (&"Gato", &4),
+                (&"Woffo", &2),
+                (&"ab", &5),
+                (&"ba", &3),
+                (&"pez", &1)
+            ]
+        );
+        assert_eq!(HashS2T::<u8, u8>::new().iter_sorted().count(), 0);
+    }
+
+    #[test]
+    fn iter_deterministic() {
+        let mut a = HashS2T::with_capacity_and_hasher(10, SeededState { seed: 1 });
+        let mut b = HashS2T::with_capacity_and_hasher(5000, SeededState { seed: 2 });
+        for (i, w) in words().enumerate() {
+            a.insert(w, i);
+        }
+        // reversed, for a different collision history too
+        for (i, w) in words().enumerate().collect::<Vec<_>>().into_iter().rev() {
+            b.insert(w, i);
+        }
+        assert_ne!(a.capacity(), b.capacity());
+        assert_ne!(a.keys().collect::<Vec<_>>(), b.keys().collect::<Vec<_>>());
+        assert!(a.iter_deterministic().eq(b.iter_deterministic()));
+        assert!(a.iter_deterministic().eq(a.iter_sorted()));
+    }
+
+    #[test]
+    fn keys() {
+        let mut h = HashS2T::new();
+        h.insert("b", 1);
+        h.insert("c", 2);
+        h.insert("a", 3);
+
+        let mut keys: Vec<_> = h.keys().copied().collect();
+        keys.sort();
+        assert_eq!(keys, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn values() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        h.insert("b", 2);
+        h.insert("ab", 3);
+        assert_eq!(h.values().sum::<i32>(), 6);
+
+        for v in h.values_mut() {
+            *v += 1;
+        }
+        assert_eq!(h.values().sum::<i32>(), 9);
+        expected_items(&h, &[("a", 2), ("b", 3), ("ab", 4)]);
+    }
+
+    #[test]
+    fn into_keys() {
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        h.insert("b".to_string(), 1);
+        h.insert("a".to_string(), 2);
+
+        let mut keys: Vec<String> = h.into_keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["a", "b"]);
+    }
+
+    #[test]
+    fn into_values() {
+        let mut h = HashS2T::new();
+        h.insert("a", "one".to_string());
+        h.insert("b", "two".to_string());
+
+        let mut values: Vec<String> = h.into_values().collect();
+        values.sort();
+        assert_eq!(values, ["one", "two"]);
+    }
+
+    #[test]
+    fn into_iter_borrowed() {
+        // a table of borrows from a local, with no 'static anywhere
+        fn lengths<'a>(words: &'a [String]) -> Vec<(&'a str, usize)> {
+            let h: HashS2T<&'a str, &'a String> = words.iter().map(|w| (w.as_str(), w)).collect();
+            let mut lengths: Vec<_> = h
+                .into_iter()
+                .map(|item| (*item.key(), item.value().len()))
+                .collect();
+            lengths.sort();
+            lengths
+        }
+        let words = vec!["Woffo".to_string(), "Gato".to_string()];
+        assert_eq!(lengths(&words), [("Gato", 4), ("Woffo", 5)]);
+
+        let local = String::from("local");
+        let mut h = HashS2T::new();
+        h.insert(1, &local);
+        for item in h {
+            assert_eq!(item.into_parts(), (1, &local));
+        }
+    }
+
+    #[test]
+    fn from_iter() {
+        let pairs = vec![("a", 1), ("b", 2), ("a", 3)];
+        let h: HashS2T<&str, i32> = pairs.into_iter().collect();
+        // last write wins
+        expected_items(&h, &[("b", 2), ("a", 3)]);
+        // sized from the iterator, not the default
+        assert_eq!(h.items.len(), 4);
+
+        let pairs = vec![("a".to_string(), 1), ("b".to_string(), 2)];
+        let h: HashS2T<String, i32> = pairs.into_iter().collect();
+        assert_eq!(h.get("a"), Some(&1));
+        assert_eq!(h.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn from_iter_borrowed_keys() {
+        let h: HashS2T<String, i32> = [("a", 1), ("b", 2), ("b", 4)].into_iter().collect();
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("a"), Some(&1));
+        assert_eq!(h.get("b"), Some(&4));
+    }
+
+    #[test]
+    fn extend() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        h.insert("b", 2);
+
+        h.extend([("b", 20), ("c", 30)]);
+        expected_items(&h, &[("a", 1), ("b", 20), ("c", 30)]);
+
+        // grows once for the whole batch
+        let mut h: HashS2T<String, usize> = HashS2T::with_capacity(3);
+        h.insert("first".to_string(), 0);
+        h.extend((0..1000).map(|i| (format!("key_{i}"), i)));
+        assert_eq!(h.items.len(), buckets_for(1001, DEFAULT_MAX_LOAD_FACTOR));
+        assert_eq!(h.get("first"), Some(&0));
+        assert_eq!(h.get("key_999"), Some(&999));
+    }
+
+    #[test]
+    fn insert_many() {
+        let mut h = HashS2T::with_capacity_and_hasher(3, CountingState::default());
+        let hashes = h.hash_builder.0.clone();
+        h.insert("Woffo".to_string(), 0);
+        let batch: Vec<_> = words()
+            .chain(["Woffo", "Gato", "Gato"])
+            .zip(1..)
+            .map(|(w, i)| (w.to_string(), i))
+            .collect();
+        let n = batch.len();
+        hashes.set(0);
+        h.insert_many(batch);
+
+        // grown once, for the whole batch plus what was there: one rehash
+        // of "Woffo", then one hash per pair
+        assert_eq!(hashes.get(), 1 + n);
+        assert_eq!(h.items.len(), buckets_for(n + 1, DEFAULT_MAX_LOAD_FACTOR));
+        // last write wins, for keys already there and within the batch
+        assert_eq!(h.get("Woffo"), Some(&(n - 2)));
+        assert_eq!(h.get("Gato"), Some(&n));
+        assert_eq!(h.get(words().next().unwrap()), Some(&1));
+    }
+
+    #[test]
+    fn extend_borrowed_keys() {
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        h.insert("a".to_string(), 1);
+
+        h.extend([("a", 10), ("b", 2)]);
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("a"), Some(&10));
+        assert_eq!(h.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn into_hash_map() {
+        let mut h: HashS2T<String, Vec<i32>> = HashS2T::new();
+        h.insert("Woffo".to_string(), vec![1]);
+        h.insert("Gato".to_string(), vec![2, 3]);
+        h.insert("ab".to_string(), vec![]);
+        // same bucket as "ab"
+        h.insert("ba".to_string(), vec![4]);
+
+        let map: HashMap<String, Vec<i32>> = h.into();
+        assert_eq!(map.len(), 4);
+        assert_eq!(map["Woffo"], vec![1]);
+        assert_eq!(map["Gato"], vec![2, 3]);
+        assert_eq!(map["ab"], Vec::<i32>::new());
+        assert_eq!(map["ba"], vec![4]);
+
+        let map = HashMap::<&str, i32>::from(HashS2T::new());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn from_hash_map() {
+        let map: HashMap<String, i32> = words().zip(0..).map(|(w, i)| (w.to_string(), i)).collect();
+        let h = HashS2T::<String, i32>::from(map.clone());
+        assert_eq!(h.len(), map.len());
+        // sized for the map up front
+        assert_eq!(
+            h.items.len(),
+            buckets_for(map.len(), DEFAULT_MAX_LOAD_FACTOR)
+        );
+        for (k, v) in &map {
+            assert_eq!(h.get(k), Some(v));
+        }
+        assert_eq!(h.get("not-a-word"), None);
+    }
+
+    #[test]
+    fn hashs2t_macro() {
+        let h = hashs2t! { "a" => 1, "b" => 2, "ab" => 3 };
+        let mut expected = HashS2T::new();
+        expected.insert("a", 1);
+        expected.insert("b", 2);
+        expected.insert("ab", 3);
+        assert_eq!(h, expected);
+        // sized for the three pairs, not the default
+        assert_eq!(h.items.len(), buckets_for(3, DEFAULT_MAX_LOAD_FACTOR));
+
+        let h = hashs2t! {
+            "Woffo".to_string() => vec![1],
+            "Gato".to_string() => vec![2, 3],
+        };
+        assert_eq!(h.len(), 2);
+        assert_eq!(h["Gato"], vec![2, 3]);
+
+        // repeated keys behave like repeated inserts
+        let h = hashs2t! { "a" => 1, "a" => 2 };
+        assert_eq!(h.len(), 1);
+        assert_eq!(h["a"], 2);
+
+        let h: HashS2T<&str, i32> = hashs2t! {};
+        assert!(h.is_empty());
+        assert_eq!(h.items.len(), DEFAULT_HASH2ST_SIZE);
+    }
+
+    #[test]
+    fn index() {
+        let mut h = HashS2T::new();
+        h.insert("Woffo", 1);
+        h.insert("Gato", 2);
+        assert_eq!(h["Woffo"], 1);
+        assert_eq!(h["Gato"], 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "no entry found for key")]
+    fn index_missing() {
+        let mut h = HashS2T::new();
+        h.insert("Woffo", 1);
+        let _ = h["Gato"];
+    }
+
+    #[test]
+    fn reserve() {
+        let mut h = HashS2T::new();
+        h.insert("a".to_string(), 1);
+        h.insert("b".to_string(), 2);
+
+        h.reserve(10_000);
+        let buckets = h.items.len();
+        assert!(buckets > DEFAULT_HASH2ST_SIZE);
+        assert!(buckets as f64 * DEFAULT_MAX_LOAD_FACTOR >= 10_002.0);
+        expected_items(&h, &[("a".to_string(), 1), ("b".to_string(), 2)]);
+
+        // no grow on the way to the reserved size
+        for i in 0..10_000 {
+            h.insert(format!("key_{i}"), i);
+        }
+        assert_eq!(h.items.len(), buckets);
+
+        // already big enough
+        h.reserve(0);
+        assert_eq!(h.items.len(), buckets);
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        h.insert("b", 2);
+
+        assert_eq!(h.try_reserve(10_000), Ok(()));
+        assert_eq!(h.capacity(), buckets_for(10_002, DEFAULT_MAX_LOAD_FACTOR));
+        expected_items(&h, &[("a", 1), ("b", 2)]);
+        let buckets = h.capacity();
+        assert_eq!(h.try_reserve(100), Ok(()));
+        assert_eq!(h.capacity(), buckets);
+
+        // more buckets than there is address space for
+        assert!(h.try_reserve(usize::MAX).is_err());
+        assert_eq!(h.capacity(), buckets);
+        assert_eq!(h.stats().collisions, 0);
+        expected_items(&h, &[("a", 1), ("b", 2)]);
+
+        let mut h = HashS2T::with_prime_capacity(2);
+        h.insert("a", 1);
+        assert_eq!(h.try_reserve(100), Ok(()));
+        // 101 / 0.75 rounds up to 135
+        assert_eq!(h.capacity(), 137);
+        assert_eq!(h["a"], 1);
+    }
+
+    #[test]
+    fn map_values() {
+        let mut h = HashS2T::with_capacity(3);
+        for (k, v) in [("ab", 1), ("ba", 2), ("Woffo", 30)] {
+            h.insert(k, v);
+        }
+        let strings = h.map_values(|v| v.to_string());
+        assert_eq!(strings.len(), 3);
+        assert_eq!(strings["ab"], "1");
+        assert_eq!(strings["ba"], "2");
+        assert_eq!(strings["Woffo"], "30");
+        // same layout
+        assert_eq!(strings.capacity(), h.capacity());
+        assert_eq!(strings.bucket_histogram(), h.bucket_histogram());
+        assert_eq!(strings.stats().collisions, h.stats().collisions);
+        let keys: Vec<_> = strings.keys().collect();
+        assert_eq!(keys, h.keys().collect::<Vec<_>>());
+        // and the source is still there
+        assert_eq!(h["Woffo"], 30);
+    }
+
+    #[test]
+    fn into_map_values() {
+        let mut h: HashS2T<String, String> = HashS2T::with_capacity(3);
+        for (k, v) in [("ab", "1"), ("ba", "2"), ("Woffo", "30")] {
+            h.insert(k.to_string(), v.to_string());
+        }
+        let key_bufs: Vec<_> = h.keys().map(|k| k.as_ptr()).collect();
+        let histogram = h.bucket_histogram();
+
+        let parsed = h.into_map_values(|v| Rc::new(v.parse::<i32>().unwrap()));
+        assert_eq!(*parsed["ab"], 1);
+        assert_eq!(*parsed["ba"], 2);
+        assert_eq!(*parsed["Woffo"], 30);
+        assert_eq!(parsed.bucket_histogram(), histogram);
+        // the very same keys, moved rather than cloned
+        let moved: Vec<_> = parsed.keys().map(|k| k.as_ptr()).collect();
+        assert_eq!(moved, key_bufs);
+    }
+
+    #[test]
+    fn filter_into() {
+        let mut h = HashS2T::new();
+        for (v, k) in ["key_a", "key_b", "other", "key_c", "more"]
+            .into_iter()
+            .enumerate()
+        {
+            h.insert(k, v);
+        }
+        let keys = h.filter_into(|k, _| k.starts_with("key_"));
+        assert_eq!(keys.len(), 3);
+        assert_eq!((keys["key_a"], keys["key_b"], keys["key_c"]), (0, 1, 3));
+        assert!(!keys.contains_key("other"));
+        assert_eq!(keys.capacity(), h.capacity());
+
+        // the source is untouched
+        assert_eq!(h.len(), 5);
+        assert_eq!((h["other"], h["more"]), (2, 4));
+        assert!(h.filter_into(|_, v| *v > 10).is_empty());
+    }
+
+    #[test]
+    fn compact() {
+        let mut h = HashS2T::with_capacity(4);
+        // all in one bucket with the byte-sum hash
+        let anagrams = ["abc", "acb", "bac", "bca", "cab", "cba"];
+        for (i, k) in anagrams.into_iter().enumerate() {
+            h.insert(k, i);
+        }
+        let buckets = h.capacity();
+        let chain_capacity =
+            |h: &HashS2T<&str, usize>| h.items.iter().flatten().map(Vec::capacity).sum::<usize>();
+        assert!(chain_capacity(&h) >= 6);
+        let before = h.memory_usage();
+
+        h.retain(|_, v| *v < 2);
+        assert!(chain_capacity(&h) >= 6);
+        h.compact();
+        assert_eq!(chain_capacity(&h), 2);
+        assert!(h.memory_usage() < before);
+        assert_eq!(h.capacity(), buckets);
+        assert_eq!((h["abc"], h["acb"], h.len()), (0, 1, 2));
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut h = HashS2T::new();
+        for i in 0..5000 {
+            h.insert(format!("key_{i}"), i);
+        }
+        for i in 10..5000 {
+            h.remove(&format!("key_{i}"));
+        }
+        let grown = h.items.len();
+
+        h.shrink_to_fit();
+        assert!(h.items.len() < grown);
+        assert_eq!(h.items.len(), 16);
+        assert_eq!(h.len(), 10);
+        for i in 0..10 {
+            assert_eq!(h.get(&format!("key_{i}")), Some(&i));
+        }
+
+        // never below one bucket
+        h.clear();
+        h.shrink_to_fit();
+        assert_eq!(h.items.len(), 1);
+        h.insert("a".to_string(), 1);
+        assert_eq!(h.get("a"), Some(&1));
+    }
+
+    #[test]
+    fn debug() {
+        let mut h = HashS2T::new();
+        assert_eq!(format!("{:?}", h), "{}");
+
+        h.insert("b", 2);
+        h.insert("a", 1);
+        h.insert("ab", 3);
+        let rendered = format!("{:?}", h);
+        let mut pairs: Vec<_> = rendered
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .unwrap()
+            .split(", ")
+            .collect();
+        pairs.sort();
+        assert_eq!(pairs, [r#""a": 1"#, r#""ab": 3"#, r#""b": 2"#]);
+
+        let item = h.iter().find(|item| item.k == "a").unwrap();
+        assert_eq!(format!("{:?}", item), r#"HashItem { k: "a", v: 1 }"#);
+    }
+
+    #[test]
+    fn display() {
+        let mut h = HashS2T::new();
+        assert_eq!(h.to_string(), "{}");
+
+        h.insert("b", 2);
+        h.insert("a", 1);
+        h.insert("ba", 4);
+        h.insert("ab", 3);
+        assert_eq!(h.to_string(), "{a: 1, ab: 3, b: 2, ba: 4}");
+
+        let h: HashS2T<i32, String> = [(10, "ten".to_string()), (2, "two".to_string())]
+            .into_iter()
+            .collect();
+        assert_eq!(format!("{h}"), "{2: two, 10: ten}");
+    }
+
+    #[test]
+    fn clone() {
+        let mut h = HashS2T::new();
+        h.insert("a", vec![1]);
+        h.insert("ab", vec![2]);
+        h.insert("ba", vec![3]);
+
+        let snapshot = h.clone();
+        assert_eq!(snapshot.items.len(), h.items.len());
+        assert_eq!(snapshot.stat_collisions, h.stat_collisions);
+
+        h.get_mut("a").unwrap().push(10);
+        h.remove("ab");
+        h.insert("c", vec![4]);
+
+        expected_items(
+            &snapshot,
+            &[("a", vec![1]), ("ab", vec![2]), ("ba", vec![3])],
+        );
+        expected_items(&h, &[("a", vec![1, 10]), ("ba", vec![3]), ("c", vec![4])]);
+    }
+
+    #[test]
+    fn partial_eq() {
+        let mut h1 = HashS2T::new();
+        h1.insert("a", 1);
+        h1.insert("ab", 2);
+        h1.insert("ba", 3);
+
+        // different order, bucket count and collision history
+        let mut h2 = HashS2T::with_capacity(1);
+        h2.insert("ba", 3);
+        h2.insert("a", 0);
+        h2.insert("ab", 2);
+        h2.insert("a", 1);
+        assert_ne!(h1.items.len(), h2.items.len());
+        assert!(h1 == h2);
+
+        h2.insert("a", 5);
+        assert!(h1 != h2);
+        h2.insert("a", 1);
+        h2.insert("b", 1);
+        assert!(h1 != h2);
+        h2.remove("b");
+        assert!(h1 == h2);
+    }
+
+    #[test]
+    fn retain() {
+        let mut h = HashS2T::new();
+        for (k, v) in [("a", 1), ("b", 2), ("ab", 3), ("ba", 4), ("c", 5)] {
+            h.insert(k, v);
+        }
+
+        h.retain(|_, v| *v % 2 == 0);
+        expected_items(&h, &[("b", 2), ("ba", 4)]);
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.items.len(), DEFAULT_HASH2ST_SIZE);
+        for k in ["a", "ab", "c"] {
+            assert_eq!(h.get(k), None);
+        }
+        // emptied buckets go back to None
+        assert!(h.items.iter().flatten().all(|items| !items.is_empty()));
+
+        // values can be changed on the way
+        h.retain(|k, v| {
+            *v *= 10;
+            *k != "b"
+        });
+        expected_items(&h, &[("ba", 40)]);
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut h = HashS2T::new();
+        for (k, v) in [
+            ("a", 1),
+            ("b", 2),
+            ("ab", 3),
+            ("ba", 4),
+            ("c", 5),
+            ("Woffo", 6),
+        ] {
+            h.insert(k, v);
+        }
+        let mut odd: Vec<_> = h.extract_if(|_, v| *v % 2 == 1).collect();
+        odd.sort();
+        assert_eq!(odd, [("a", 1), ("ab", 3), ("c", 5)]);
+        expected_items(&h, &[("b", 2), ("ba", 4), ("Woffo", 6)]);
+        assert_eq!(h.len(), 3);
+        assert_eq!(h.stats().collisions, 0);
+        assert!(h.items.iter().flatten().all(|items| !items.is_empty()));
+
+        // kept values can be changed on the way, and dropping the
+        // iterator still takes the matches out
+        let _ = h.extract_if(|k, v| {
+            *v *= 10;
+            *k == "b"
+        });
+        expected_items(&h, &[("ba", 40), ("Woffo", 60)]);
+        assert_eq!(h.extract_if(|_, _| false).count(), 0);
+    }
+
+    #[test]
+    fn drain() {
+        let mut h = HashS2T::new();
+        for (k, v) in [("a", 1), ("b", 2), ("ab", 3), ("ba", 4)] {
+            h.insert(k, v);
+        }
+
+        let mut drained: Vec<_> = h.drain().collect();
+        drained.sort();
+        assert_eq!(drained, [("a", 1), ("ab", 3), ("b", 2), ("ba", 4)]);
+
+        assert!(h.is_empty());
+        assert_eq!(h.stat_collisions, 0);
+        assert_eq!(h.items.len(), DEFAULT_HASH2ST_SIZE);
+        assert!(h.items.iter().all(Option::is_none));
+
+        // still usable afterwards
+        h.insert("a", 5);
+        expected_items(&h, &[("a", 5)]);
+    }
+
+    #[test]
+    fn drain_dropped_early() {
+        let mut h = HashS2T::new();
+        for (k, v) in [("a", 1), ("b", 2), ("ab", 3), ("ba", 4)] {
+            h.insert(k, v);
+        }
+
+        let mut drain = h.drain();
+        assert!(drain.next().is_some());
+        drop(drain);
+
+        assert!(h.is_empty());
+        assert!(h.items.iter().all(Option::is_none));
+        expected_items(&h, &[]);
+    }
+
+    #[test]
+    fn drain_forgotten() {
+        let mut h = HashS2T::new();
+        for (k, v) in [("a", 1), ("b", 2), ("ab", 3), ("ba", 4)] {
+            h.insert(k, v);
+        }
+        // leaked before taking anything: nothing changed
+        std::mem::forget(h.drain());
+        assert_eq!(h.len(), 4);
+        assert_eq!(h.iter().count(), 4);
+        assert_eq!(h.stat_collisions, 1);
+
+        // leaked halfway: the counters match what's left
+        let mut drain = h.drain();
+        let (k, _) = drain.next().unwrap();
+        std::mem::forget(drain);
+        assert_eq!(h.iter().count(), h.len());
+        assert!(!h.contains_key(k));
+        let used = h.items.iter().flatten().count();
+        assert_eq!(h.stat_collisions, h.len() - used);
+        h.insert(k, 0);
+        assert_eq!(h.iter().count(), h.len());
+    }
+
+    #[test]
+    fn get() {
+        let mut h = HashS2T::new();
+        // works with no contents
+        let _opt = h.get("gg");
+
+        h.insert("a", 1);
+        h.insert("R", 42);
+        h.insert("c", 3);
+
+        assert_eq!(h.get("R"), Some(&42));
+        assert_eq!(h.get("Q"), None);
+        assert_eq!((h.peek("R"), h.peek("Q")), (Some(&42), None));
+    }
+
+    #[test]
+    fn get_key_value() {
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        assert_eq!(h.get_key_value("Woffo"), None);
+
+        h.insert("Woffo".to_string(), 1);
+        let (k, v) = h.get_key_value("Woffo").unwrap();
+        assert_eq!(k, "Woffo");
+        assert_eq!(*v, 1);
+        assert_eq!(h.get_key_value("Gato"), None);
+    }
+
+    #[test]
+    fn non_string_keys() {
+        let mut h = HashS2T::new();
+        h.insert(7u64, "seven");
+        h.insert(u64::MAX, "max");
+        assert_eq!(h.get(&7), Some(&"seven"));
+        assert_eq!(h.get(&u64::MAX), Some(&"max"));
+        assert_eq!(h.get(&8), None);
+
+        let mut h = HashS2T::new();
+        h.insert((1u32, 2u32), 12);
+        h.insert((2, 1), 21);
+        assert_eq!(h.get(&(1, 2)), Some(&12));
+        assert_eq!(h.get(&(2, 1)), Some(&21));
+
+        #[derive(Hash, PartialEq, Eq)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+        let mut h = HashS2T::new();
+        h.insert(Point { x: 0, y: 1 }, "up");
+        h.insert(Point { x: 1, y: 0 }, "right");
+        assert_eq!(h.get(&Point { x: 0, y: 1 }), Some(&"up"));
+        assert_eq!(h.remove(&Point { x: 1, y: 0 }), Some("right"));
+        assert_eq!(h.len(), 1);
+    }
+
+    #[test]
+    fn borrowed_lookup() {
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        h.insert("Woffo".to_string(), 1);
+        h.insert("Gato".to_string(), 2);
+
+        // String keys, &str lookups
+        assert_eq!(h.get("Woffo"), Some(&1));
+        assert!(h.contains_key("Gato"));
+        *h.get_mut("Gato").unwrap() += 1;
+        assert_eq!(h.remove("Gato"), Some(3));
+        assert_eq!(h.get("Gato"), None);
+    }
+
+    #[test]
+    fn owned_keys_are_moved_in() {
+        // insert takes the key by value: a String is stored as it is
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        let k = String::from("Woffo");
+        let buf = k.as_ptr();
+        assert_eq!(h.insert(k, 1), None);
+        assert_eq!(h.insert("Woffo".to_string(), 2), Some(1));
+        // overwriting replaces the value only, the first key is kept
+        let (stored, v) = h.get_key_value("Woffo").unwrap();
+        assert_eq!((stored.as_ptr(), *v), (buf, 2));
+
+        // and with Cow keys, 'static strs are never copied
+        let mut h: HashS2T<Cow<'static, str>, i32> = HashS2T::new();
+        h.insert(Cow::Borrowed("Gato"), 1);
+        h.insert(Cow::Owned("Pez".to_string()), 2);
+        assert_eq!(h.insert("Gato".into(), 3), Some(1));
+        let (gato, _) = h.get_key_value("Gato").unwrap();
+        assert!(matches!(gato, Cow::Borrowed(_)));
+        assert_eq!((h["Gato"], h["Pez"]), (3, 2));
+    }
+
+    #[test]
+    fn borrowed_lookup_bytes() {
+        let mut h: HashS2T<Box<[u8]>, i32> = HashS2T::new();
+        h.insert(Box::from(&b"\x00\xff"[..]), 1);
+        h.insert(Box::from(&b"Woffo"[..]), 2);
+
+        // Box<[u8]> keys, &[u8] lookups: Box<[u8]> hashes like the slice
+        let k: &[u8] = b"\x00\xff";
+        assert_eq!(h.get(k), Some(&1));
+        assert_eq!(h[&b"Woffo"[..]], 2);
+        assert!(h.contains_key(&b"Woffo"[..]));
+        assert!(!h.contains_key(&b"woffo"[..]));
+        let (stored, _) = h.get_key_value(k).unwrap();
+        assert_eq!(&stored[..], k);
+        assert_eq!(h.remove_entry(k), Some((Box::from(k), 1)));
+        assert_eq!(h.remove(k), None);
+        assert_eq!(h.len(), 1);
+    }
+
+    #[test]
+    fn with_hasher() {
+        let anagrams = [
+            "abcd", "abdc", "acbd", "acdb", "adbc", "adcb", "bacd", "badc", "bcad", "bcda", "bdac",
+            "bdca", "cabd", "cadb", "cbad", "cbda", "cdab", "cdba", "dabc", "dacb", "dbac", "dbca",
+            "dcab", "dcba",
+        ];
+
+        let mut h = HashS2T::new();
+        for (v, k) in anagrams.into_iter().enumerate() {
+            h.insert(k, v);
+        }
+        // the char-sum hash piles all of them in one bucket
+        assert_eq!(h.items.iter().filter(|node| node.is_some()).count(), 1);
+
+        let mut h = HashS2T::with_hasher(RandomState::new());
+        for (v, k) in anagrams.into_iter().enumerate() {
+            h.insert(k, v);
+        }
+        assert!(h.items.iter().filter(|node| node.is_some()).count() > 1);
+        for (v, k) in anagrams.into_iter().enumerate() {
+            assert_eq!(h.get(k), Some(&v));
+        }
+    }
+
+    #[test]
+    fn type_level_hasher() {
+        let mut h = HashS2T::<String, i32, RandomState>::default();
+        h.insert("Woffo".to_string(), 1);
+        h.insert("Gato".to_string(), 2);
+        assert_eq!(h.get("Woffo"), Some(&1));
+        assert_eq!(h.get("Gato"), Some(&2));
+        assert_eq!(h.get("Pez"), None);
+
+        let h: HashS2T<&str, usize, Djb2State> = words().zip(0..).collect();
+        assert_eq!(h.get(words().next().unwrap()), Some(&0));
+
+        // the stateless hashers take no room: nothing is stored to pick them
+        assert_eq!(
+            core::mem::size_of::<HashS2T<&str, i32, Fnv1aState>>(),
+            core::mem::size_of::<HashS2T<&str, i32>>()
+        );
+        assert_eq!(core::mem::size_of::<CharSumState>(), 0);
+    }
+
+    #[test]
+    fn mul_mix() {
+        // str keys end with a 0xff byte
+        assert_eq!(super::mul_mix("", 256), 255);
+        assert_eq!(super::mul_mix("a", 1 << 20), 97 * 31 + 255);
+        assert_ne!(super::mul_mix("ab", 256), super::mul_mix("ba", 256));
+        // the legacy name
+        assert_eq!(
+            super::default_hash("Woffo", 10),
+            super::mul_mix("Woffo", 10)
+        );
+    }
+
+    #[test]
+    fn mul_mix_distribution() {
+        // before: the default byte sum; after: the multiply-mix. Both
+        // tables measured, with the same bucket count.
+        let mut before = HashS2T::new();
+        let mut after = HashS2T::with_hasher(MulMixState::default());
+        for (v, k) in words().enumerate() {
+            before.insert(k, v);
+            after.insert(k, v);
+        }
+        assert_eq!(before.capacity(), after.capacity());
+        let (before_c, after_c) = (before.stats().collisions, after.stats().collisions);
+        assert!(
+            after_c * 2 < before_c,
+            "after: {after_c}, before: {before_c}"
+        );
+        // and mul_mix is where that table keeps each key
+        for k in words() {
+            assert_eq!(super::mul_mix(k, after.capacity()), after.bucket(k));
+            assert_eq!(after.get(k), before.get(k));
+        }
+    }
+
+    #[test]
+    fn mul_mix_long_string() {
+        // 4 MB of the highest codepoint: a plain sum would overflow on
+        // 32-bit targets, and the multiply overflows anywhere, so this
+        // only passes in debug builds if everything wraps. The results are
+        // pinned so the wrapping stays the same too.
+        let s: String = core::iter::repeat_n('\u{10FFFF}', 1_000_000).collect();
+        assert_eq!(super::mul_mix(&s, 1000), 191);
+        assert_eq!(super::mul_mix(&s, 1009), 525);
+        // 0xf4 + 0x8f + 0xbf + 0xbf per char, and the str terminator
+        assert_eq!(CharSumState::default().hash_one(s.as_str()), 769_000_255);
+    }
+
+    #[test]
+    fn fnv1a() {
+        // known FNV-1a 64 values
+        let mut hasher = Fnv1aHasher::default();
+        assert_eq!(hasher.finish(), FNV_OFFSET_BASIS);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+
+        assert_ne!(super::fnv1a("ab", 256), super::fnv1a("ba", 256));
+        assert!(super::fnv1a("Woffo", 10) < 10);
+    }
+
+    #[test]
+    fn fnv1a_is_the_table_bucket() {
+        let mut h = HashS2T::with_hasher(Fnv1aState::default());
+        for (v, k) in words().enumerate() {
+            h.insert(k, v);
+        }
+        for k in words() {
+            assert_eq!(super::fnv1a(k, h.items.len()), h.bucket(k));
+        }
+    }
+
+    #[test]
+    fn fnv1a_collisions() {
+        let mut char_sum = HashS2T::new();
+        let mut fnv = HashS2T::with_hasher(Fnv1aState::default());
+        for (v, k) in words().enumerate() {
+            char_sum.insert(k, v);
+            fnv.insert(k, v);
+        }
+        assert_eq!(char_sum.items.len(), fnv.items.len());
+        assert!(
+            fnv.stat_collisions * 2 < char_sum.stat_collisions,
+            "fnv: {}, char sum: {}",
+            fnv.stat_collisions,
+            char_sum.stat_collisions
+        );
+        for (v, k) in words().enumerate() {
+            assert_eq!(fnv.get(k), Some(&v));
+        }
+    }
+
+    #[test]
+    fn rehash() {
+        let used_buckets =
+            |items: &[HashNode<Vec<HashItem<&str, usize>>>]| items.iter().flatten().count();
+        let mut h = HashS2T::new();
+        for (v, k) in words().enumerate() {
+            h.insert(k, v);
+        }
+        h.max_load_factor = 0.9;
+        let (buckets, collisions) = (h.items.len(), h.stat_collisions);
+
+        let h = h.rehash(Fnv1aState::default());
+        assert_eq!(h.items.len(), buckets);
+        assert_eq!(h.max_load_factor, 0.9);
+        assert!(h.stat_collisions < collisions);
+        assert_eq!(h.stat_collisions, h.len() - used_buckets(&h.items));
+        for (v, k) in words().enumerate() {
+            assert_eq!(h.get(k), Some(&v));
+        }
+
+        // and back
+        let h = h.rehash(CharSumState::default());
+        assert_eq!(h.stat_collisions, collisions);
+        assert_eq!(h.len(), words().count());
+    }
+
+    #[test]
+    fn djb2() {
+        let mut hasher = Djb2Hasher::default();
+        hasher.write(b"ab");
+        assert_eq!(hasher.finish(), (5381 * 33 + 97) * 33 + 98);
+        assert_ne!(super::djb2("ab", 256), super::djb2("ba", 256));
+
+        let mut h = HashS2T::with_hasher(Djb2State::default());
+        for (v, k) in words().enumerate() {
+            h.insert(k, v);
+        }
+        for (v, k) in words().enumerate() {
+            assert_eq!(h.get(k), Some(&v));
+            assert_eq!(super::djb2(k, h.items.len()), h.bucket(k));
+        }
+    }
+
+    #[test]
+    fn djb2_long_string() {
+        // 33^13 alone is already past u64::MAX
+        let long = "z".repeat(100_000);
+        let i = super::djb2(&long, 256);
+        assert!(i < 256);
+        assert_eq!(i, super::djb2(&long, 256));
+        assert_ne!(i, super::djb2(&long[1..], 256));
+    }
+
+    #[test]
+    fn with_seed() {
+        let buckets = |h: &HashS2T<&str, usize, SeededState>| -> Vec<usize> {
+            words().map(|k| h.bucket(k)).collect()
+        };
+        let mut h1 = HashS2T::with_seed(1);
+        let mut h2 = HashS2T::with_seed(2);
+        let mut h3 = HashS2T::with_seed(1);
+        for (v, k) in words().enumerate() {
+            h1.insert(k, v);
+            h2.insert(k, v);
+            h3.insert(k, v);
+        }
+        assert_eq!(h1.items.len(), h2.items.len());
+        assert_ne!(buckets(&h1), buckets(&h2));
+        // same seed, same layout
+        assert_eq!(buckets(&h1), buckets(&h3));
+
+        for (v, k) in words().enumerate() {
+            assert_eq!(h1.get(k), Some(&v));
+            assert_eq!(h2.get(k), Some(&v));
+        }
+    }
+
+    #[test]
+    fn stats() {
+        let mut h = HashS2T::new();
+        assert_eq!(
+            h.stats(),
+            Stats {
+                entries: 0,
+                buckets: DEFAULT_HASH2ST_SIZE,
+                used_buckets: 0,
+                max_bucket_len: 0,
+                avg_bucket_len: 0.0,
+                collisions: 0,
+            }
+        );
+
+        // three anagrams in one bucket, "a" and "b" alone
+        for (k, v) in [("abc", 1), ("bca", 2), ("cab", 3), ("a", 4), ("b", 5)] {
+            h.insert(k, v);
+        }
+        assert_eq!(
+            h.stats(),
+            Stats {
+                entries: 5,
+                buckets: DEFAULT_HASH2ST_SIZE,
+                used_buckets: 3,
+                max_bucket_len: 3,
+                avg_bucket_len: 5.0 / 3.0,
+                collisions: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn bucket_histogram() {
+        let mut h = HashS2T::with_capacity(3);
+        assert_eq!(h.bucket_histogram(), [0, 0, 0, 0]);
+        // str hashing adds a 0xff byte: "abc" and "bca" sum to 549, bucket
+        // 1, and "a" to 352, bucket 0
+        for (k, v) in [("abc", 1), ("bca", 2), ("a", 3)] {
+            h.insert(k, v);
+        }
+        assert_eq!(h.bucket_histogram(), [1, 2, 0, 0]);
+
+        for w in words() {
+            h.insert(w, 0);
+        }
+        let histogram = h.bucket_histogram();
+        assert_eq!(histogram.len(), h.capacity());
+        assert_eq!(histogram.iter().sum::<usize>(), h.len());
+        assert_eq!(histogram.iter().max(), Some(&h.stats().max_bucket_len));
+    }
+
+    #[test]
+    fn capacity() {
+        let mut h = HashS2T::new();
+        assert_eq!(h.capacity(), DEFAULT_HASH2ST_SIZE);
+        for i in 0..1000 {
+            h.insert(i, i);
+        }
+        assert_eq!(h.capacity(), 2048);
+        assert_eq!(h.len(), 1000);
+        assert_eq!(HashS2T::<u8, u8>::with_capacity(100).capacity(), 256);
+    }
+
+    #[test]
+    fn load_factor() {
+        let mut h = HashS2T::new();
+        assert_eq!(h.load_factor(), 0.0);
+        for i in 0..64 {
+            h.insert(i, i);
+        }
+        assert_eq!(h.load_factor(), 0.25);
+
+        // no buckets at all
+        let h: HashS2T<&str, ()> = HashS2T::with_buckets_and_hasher(0, CharSumState::default());
+        assert_eq!(h.load_factor(), 0.0);
+    }
+
+    #[test]
+    fn stat_collisions() {
+        let used_buckets = |h: &HashS2T<&str, i32>| h.items.iter().flatten().count();
+        let mut h = HashS2T::new();
+        // "abc", "bca", "cab" share a bucket
+        h.insert("abc", 1);
+        assert_eq!(h.stat_collisions, 0);
+        h.insert("bca", 2);
+        h.insert("cab", 3);
+        h.insert("a", 4);
+        assert_eq!(h.stat_collisions, 2);
+
+        // overwrite
+        h.insert("bca", 20);
+        *h.entry("cab").or_insert(0) += 1;
+        assert_eq!(h.stat_collisions, 2);
+
+        // remove, from a shared bucket and then a lone one
+        h.remove("abc");
+        assert_eq!(h.stat_collisions, 1);
+        h.remove("a");
+        assert_eq!(h.stat_collisions, 1);
+        h.remove("missing");
+        assert_eq!(h.stat_collisions, 1);
+        assert_eq!(h.stat_collisions, h.len() - used_buckets(&h));
+
+        // resize
+        h.insert("abc", 1);
+        h.insert("a", 4);
+        h.resize(1024);
+        assert_eq!(h.stat_collisions, 2);
+        h.shrink_to_fit();
+        assert_eq!(h.stat_collisions, h.len() - used_buckets(&h));
+
+        // retain
+        let mut h = HashS2T::new();
+        for (k, v) in [
+            ("abc", 1),
+            ("bca", 2),
+            ("cab", 3),
+            ("a", 4),
+            ("b", 5),
+            ("ab", 6),
+            ("ba", 7),
+        ] {
+            h.insert(k, v);
+        }
+        assert_eq!(h.stat_collisions, 3);
+        h.retain(|k, _| !["abc", "bca", "ab", "ba"].contains(k));
+        assert_eq!(h.stat_collisions, 0);
+        assert_eq!(h.len(), 3);
+    }
+
+    #[test]
+    fn reset_stats() {
+        let mut h = HashS2T::new();
+        // "abc", "bca", "cab" share a bucket, and so do "ab" and "ba"
+        for (k, v) in [("abc", 1), ("bca", 2), ("ab", 3)] {
+            h.insert(k, v);
+        }
+        assert_eq!(h.stats().collisions, 1);
+        h.reset_stats();
+        assert_eq!(h.stats().collisions, 0);
+        assert_eq!((h.len(), h["abc"], h["bca"], h["ab"]), (3, 1, 2, 3));
+
+        h.insert("cab", 4);
+        h.insert("ba", 5);
+        h.insert("Woffo", 6);
+        assert_eq!(h.stats().collisions, 2);
+        // the stats derived from the buckets are not counters
+        assert_eq!(h.stats().max_bucket_len, 3);
+
+        // removing what was counted before the reset doesn't underflow
+        h.reset_stats();
+        h.remove("abc");
+        h.retain(|k, _| *k != "ab");
+        assert_eq!(h.stats().collisions, 0);
+        assert_eq!(h.len(), 4);
+    }
+
+    #[test]
+    fn len() {
+        let mut h = HashS2T::new();
+        assert_eq!(h.len(), 0);
+        assert!(h.is_empty());
+
+        h.insert("a", 1);
+        h.insert("b", 2);
+        assert_eq!(h.len(), 2);
+        assert!(!h.is_empty());
+
+        // overwriting does not count twice
+        h.insert("a", 3);
+        h.insert("b", 4);
+        assert_eq!(h.len(), 2);
+
+        h.remove("a");
+        assert_eq!(h.len(), 1);
+        // removing something missing does not change it
+        h.remove("a");
+        assert_eq!(h.len(), 1);
+        h.remove("b");
+        assert!(h.is_empty());
+    }
+
+    #[test]
+    fn clear() {
+        let mut h = HashS2T::new();
+        let keys = ["a", "b", "ab", "ba"];
+        for (v, k) in keys.into_iter().enumerate() {
+            h.insert(k, v);
+        }
+        assert!(h.stat_collisions > 0);
+
+        h.clear();
+        assert_eq!(h.items.len(), DEFAULT_HASH2ST_SIZE);
+        assert!(h.is_empty());
+        assert_eq!(h.stat_collisions, 0);
+        for k in keys {
+            assert_eq!(h.get(k), None);
+        }
+
+        // still usable afterwards
+        h.insert("a", 42);
+        assert_eq!(h.get("a"), Some(&42));
+    }
+
+    #[test]
+    fn entry_or_insert() {
+        let mut h = HashS2T::new();
+        for _ in 0..3 {
+            *h.entry("x").or_insert(0) += 1;
+        }
+        *h.entry("y").or_insert(0) += 1;
+
+        assert_eq!(h.get("x"), Some(&3));
+        assert_eq!(h.get("y"), Some(&1));
+        assert_eq!(h.len(), 2);
+    }
+
+    #[test]
+    fn entry_or_insert_with() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+
+        assert_eq!(*h.entry("a").or_insert_with(|| unreachable!()), 1);
+        assert_eq!(*h.entry("b").or_insert_with(|| 2), 2);
+        expected_items(&h, &[("a", 1), ("b", 2)]);
+    }
+
+    #[test]
+    fn entry_or_default() {
+        let mut h: HashS2T<&str, usize> = HashS2T::new();
+        for w in "el perro y el gato y el pez".split(' ') {
+            *h.entry(w).or_default() += 1;
+        }
+        assert_eq!(h.len(), 5);
+        assert_eq!(h["el"], 3);
+        assert_eq!(h["y"], 2);
+        assert_eq!(h["perro"], 1);
+        assert_eq!(h["pez"], 1);
+
+        let mut h: HashS2T<&str, Vec<i32>> = HashS2T::new();
+        h.entry("a").or_default().push(1);
+        h.entry("a").or_default().push(2);
+        assert_eq!(h["a"], [1, 2]);
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut calls = 0;
+        let mut h = HashS2T::new();
+
+        *h.get_or_insert_with("a", || {
+            calls += 1;
+            1
+        }) += 10;
+        assert_eq!(calls, 1);
+
+        let v = h.get_or_insert_with("a", || {
+            calls += 1;
+            100
+        });
+        assert_eq!(*v, 11);
+        assert_eq!(calls, 1);
+        assert_eq!(h.len(), 1);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut h = HashS2T::new();
+        *h.try_insert("a", 1).unwrap() += 1;
+        assert_eq!(h.get("a"), Some(&2));
+    }
+
+    #[test]
+    fn try_insert_occupied() {
+        let mut h = HashS2T::new();
+        h.insert("a", vec![1]);
+
+        let err = h.try_insert("a", vec![2]).unwrap_err();
+        assert_eq!(err.entry.get(), &vec![1]);
+        assert_eq!(
+            err.to_string(),
+            r#"failed to insert [2], key "a" already exists with value [1]"#
+        );
+        // the rejected value comes back
+        let OccupiedError { value, .. } = err;
+        assert_eq!(value, vec![2]);
+
+        expected_items(&h, &[("a", vec![1])]);
+    }
+
+    #[test]
+    fn entry_and_modify() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+
+        h.entry("a").and_modify(|v| *v += 10).or_insert(0);
+        h.entry("b").and_modify(|v| *v += 10).or_insert(0);
+        expected_items(&h, &[("b", 0), ("a", 11)]);
+
+        match h.entry("a") {
+            Entry::Occupied(entry) => assert_eq!(entry.get(), &11),
+            Entry::Vacant(_) => panic!("a must be occupied"),
+        }
+    }
+
+    #[test]
+    fn entry_and_modify_counting() {
+        let text = "the dog and the cat and the fish and the dog";
+        let mut h = HashS2T::new();
+        let mut modified = 0;
+        for w in text.split(' ') {
+            h.entry(w)
+                .and_modify(|v| {
+                    modified += 1;
+                    *v += 1
+                })
+                .or_insert(1);
+        }
+        expected_items(
+            &h,
+            &[("cat", 1), ("fish", 1), ("dog", 2), ("and", 3), ("the", 4)],
+        );
+        // only repeated words hit the closure
+        assert_eq!(modified, 11 - 5);
+    }
+
+    #[test]
+    fn entry_key() {
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        h.insert("Woffo".to_string(), 1);
+
+        let entry = h.entry("Gato".to_string());
+        assert!(matches!(entry, Entry::Vacant(_)));
+        assert_eq!(entry.key(), "Gato");
+        let Entry::Vacant(vacant) = entry else {
+            unreachable!()
+        };
+        assert_eq!(vacant.key(), "Gato");
+        assert_eq!(vacant.into_key(), "Gato");
+        assert!(!h.contains_key("Gato"));
+
+        let entry = h.entry("Woffo".to_string());
+        assert!(matches!(entry, Entry::Occupied(_)));
+        assert_eq!(entry.key(), "Woffo");
+        *entry.or_insert(0) += 1;
+        assert_eq!(h.get("Woffo"), Some(&2));
+    }
+
+    #[test]
+    fn entry_collision_chain() {
+        let mut h = HashS2T::new();
+        // all anagrams share a bucket
+        *h.entry("abc").or_insert(0) += 1;
+        *h.entry("bca").or_insert(0) += 2;
+        *h.entry("abc").or_insert(0) += 1;
+
+        expected_items(&h, &[("abc", 2), ("bca", 2)]);
+        assert_eq!(h.stat_collisions, 1);
+    }
+
+    #[test]
+    fn contains_key() {
+        // values are neither Copy nor Debug
+        struct Opaque;
+
+        let mut h = HashS2T::new();
+        // works with no contents
+        assert!(!h.contains_key("gg"));
+
+        h.insert("a", Opaque);
+        h.insert("b", Opaque);
+
+        assert!(h.contains_key("a"));
+        assert!(h.contains_key("b"));
+        assert!(!h.contains_key("c"));
+    }
+
+    #[test]
+    fn contains_value() {
+        let mut h = HashS2T::new();
+        h.insert("Woffo", "dog".to_string());
+        h.insert("Gato", "cat".to_string());
+        // same bucket as "ab"
+        h.insert("ab", "x".to_string());
+        h.insert("ba", "y".to_string());
+
+        assert!(h.contains_value(&"cat".to_string()));
+        assert!(h.contains_value(&"y".to_string()));
+        assert!(!h.contains_value(&"fish".to_string()));
+
+        h.insert("Gato", "kitten".to_string());
+        assert!(!h.contains_value(&"cat".to_string()));
+        assert!(!HashS2T::<&str, i32>::new().contains_value(&0));
+    }
+
+    #[test]
+    fn contains_key_empty_table() {
+        let h: HashS2T<&str, ()> = HashS2T {
+            items: Vec::new(),
+            len: 0,
+            stat_collisions: 0,
+            hash_builder: CharSumState::default(),
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+            growth_factor: DEFAULT_GROWTH_FACTOR,
+            prime_buckets: false,
+            marker: PhantomData,
+        };
+        assert!(!h.contains_key("a"));
+    }
+
+    #[test]
+    fn remove() {
+        let mut h = HashS2T::new();
+        // works with no contents
+        assert_eq!(h.remove("gg"), None);
+
+        h.insert("a", 1);
+        h.insert("b", 2);
+
+        assert_eq!(h.remove("a"), Some(1));
+        assert_eq!(h.remove("a"), None);
+        assert_eq!(h.get("a"), None);
+
+        // emptied bucket goes back to None
+        let i = h.bucket("a");
+        assert!(h.items[i].is_none());
+
+        expected_items(&h, &[("b", 2)]);
+    }
+
+    #[test]
+    fn get_disjoint_mut() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        h.insert("b", 2);
+        // same bucket as "ab"
+        h.insert("ab", 3);
+        h.insert("ba", 4);
+
+        let [a, ba, ab] = h.get_disjoint_mut(["a", "ba", "ab"]);
+        let (a, ba, ab) = (a.unwrap(), ba.unwrap(), ab.unwrap());
+        std::mem::swap(ab, ba);
+        *a += 10;
+        expected_items(&h, &[("b", 2), ("ba", 3), ("ab", 4), ("a", 11)]);
+    }
+
+    #[test]
+    fn get_disjoint_mut_any_bucket_order() {
+        let mut h = HashS2T::with_hasher(Fnv1aState::default());
+        for (v, k) in words().enumerate() {
+            h.insert(k, v);
+        }
+        // highest bucket first, whatever the order they're asked in
+        let mut ks: Vec<&str> = words().take(20).collect();
+        ks.sort_by_key(|k| std::cmp::Reverse(h.bucket(k)));
+        let ks: [&str; 20] = ks.try_into().unwrap();
+        for v in h.get_disjoint_mut(ks) {
+            *v.unwrap() += 1000;
+        }
+        for (v, k) in words().enumerate().take(20) {
+            assert_eq!(h.get(k), Some(&(v + 1000)));
+        }
+    }
+
+    #[test]
+    fn get_disjoint_mut_missing() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        let [a, missing] = h.get_disjoint_mut(["a", "c"]);
+        assert_eq!(a, Some(&mut 1));
+        assert_eq!(missing, None);
+
+        let mut h: HashS2T<&str, i32> = HashS2T::with_buckets_and_hasher(0, Default::default());
+        assert_eq!(h.get_disjoint_mut(["a", "b"]), [None, None]);
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate keys in get_disjoint_mut")]
+    fn get_disjoint_mut_duplicate() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        h.insert("b", 2);
+        let _ = h.get_disjoint_mut(["a", "b", "a"]);
+    }
+
+    #[test]
+    fn swap() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        h.insert("b", 2);
+        // same bucket as "ba"
+        h.insert("ab", 3);
+        h.insert("ba", 4);
+
+        assert!(h.swap("a", "b"));
+        assert!(h.swap("ab", "ba"));
+        expected_items(&h, &[("b", 1), ("a", 2), ("ba", 3), ("ab", 4)]);
+    }
+
+    #[test]
+    fn swap_missing() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        assert!(!h.swap("a", "c"));
+        assert!(!h.swap("c", "a"));
+        assert!(!h.swap("c", "d"));
+        expected_items(&h, &[("a", 1)]);
+    }
+
+    #[test]
+    fn swap_same_key() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        assert!(h.swap("a", "a"));
+        assert!(!h.swap("c", "c"));
+        expected_items(&h, &[("a", 1)]);
+    }
+
+    #[test]
+    fn rename_key() {
+        let mut h = HashS2T::new();
+        h.insert("Woffo", 1);
+        h.insert("b", 2);
+        assert!(h.rename_key("Woffo", "Gato"));
+        expected_items(&h, &[("Gato", 1), ("b", 2)]);
+        assert!(!h.contains_key("Woffo"));
+        // found in its new bucket
+        assert_eq!(h.get_key_value("Gato"), Some((&"Gato", &1)));
+
+        assert!(h.rename_key("Gato", "Gato"));
+        expected_items(&h, &[("Gato", 1), ("b", 2)]);
+    }
+
+    #[test]
+    fn rename_key_onto_existing() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        h.insert("b", 2);
+        assert!(h.rename_key("a", "b"));
+        expected_items(&h, &[("b", 1)]);
+        assert_eq!(h.stats().collisions, 0);
+    }
+
+    #[test]
+    fn rename_key_missing() {
+        let mut h = HashS2T::new();
+        h.insert("a", 1);
+        assert!(!h.rename_key("c", "a"));
+        assert!(!h.rename_key("c", "d"));
+        expected_items(&h, &[("a", 1)]);
+    }
+
+    #[test]
+    fn remove_entry() {
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        h.insert("ab".to_string(), 1);
+        h.insert("ba".to_string(), 2);
+
+        let (k, v) = h.remove_entry("ba").unwrap();
+        assert_eq!((k.as_str(), v), ("ba", 2));
+        assert_eq!(h.remove_entry("ba"), None);
+        assert_eq!(h.stat_collisions, 0);
+        expected_items(&h, &[("ab".to_string(), 1)]);
+    }
+
+    #[test]
+    fn remove_from_collision_chain() {
+        let mut h = HashS2T::new();
+        // anagrams land in the same bucket with the char-sum hash
+        h.insert("abc", 1);
+        h.insert("bca", 2);
+        h.insert("cab", 3);
+        let i = h.bucket("abc");
+        assert_eq!(h.items[i].as_ref().map(Vec::len), Some(3));
+
+        assert_eq!(h.remove("abc"), Some(1));
+        assert_eq!(h.items[i].as_ref().map(Vec::len), Some(2));
+        assert_eq!(h.get("bca"), Some(&2));
+        assert_eq!(h.get("cab"), Some(&3));
+
+        assert_eq!(h.remove("cab"), Some(3));
+        assert_eq!(h.remove("bca"), Some(2));
+        assert!(h.items[i].is_none());
+        expected_items(&h, &[]);
+    }
+
+    fn merge_inputs() -> (HashS2T<&'static str, i32>, HashS2T<&'static str, i32>) {
+        let left = hashs2t! { "a" => 1, "b" => 2, "ab" => 3 };
+        // "ba" shares a bucket with "ab"
+        let right = hashs2t! { "b" => 20, "ab" => 30, "ba" => 40 };
+        (left, right)
+    }
+
+    #[test]
+    fn merge_sum() {
+        let (mut left, right) = merge_inputs();
+        left.merge(right, |_, cur, new| *cur += new);
+        expected_items(&left, &[("a", 1), ("b", 22), ("ab", 33), ("ba", 40)]);
+        assert_eq!(left.stat_collisions, 1);
+    }
+
+    #[test]
+    fn merge_keep_left() {
+        let (mut left, right) = merge_inputs();
+        left.merge(right, |_, _, _| {});
+        expected_items(&left, &[("a", 1), ("b", 2), ("ab", 3), ("ba", 40)]);
+    }
+
+    #[test]
+    fn merge_keep_right() {
+        let (mut left, right) = merge_inputs();
+        let mut clashes = Vec::new();
+        left.merge(right, |k, cur, new| {
+            clashes.push(*k);
+            *cur = new;
+        });
+        expected_items(&left, &[("a", 1), ("b", 20), ("ab", 30), ("ba", 40)]);
+        clashes.sort();
+        assert_eq!(clashes, ["ab", "b"]);
+    }
+
+    #[test]
+    fn send_sync() {
+        fn assert_send<T: Send>() {}
+        fn assert_sync<T: Sync>() {}
+        type Table = HashS2T<String, Vec<u8>>;
+        type Listed = HashS2T<String, u8, SeededState, LinkedList<HashItem<String, u8>>>;
+
+        assert_send::<Table>();
+        assert_sync::<Table>();
+        assert_send::<Listed>();
+        assert_sync::<Listed>();
+        assert_send::<HashS2T<&str, u8, Fnv1aState>>();
+        assert_sync::<HashS2T<&str, u8, Djb2State>>();
+        // and everything borrowing or consuming one
+        assert_send::<Iter<'_, String, Vec<u8>>>();
+        assert_sync::<Iter<'_, String, Vec<u8>>>();
+        assert_send::<IntoIter<String, u8, LinkedList<HashItem<String, u8>>>>();
+        assert_send::<Drain<'_, String, Vec<u8>>>();
+        assert_send::<Entry<'_, String, Vec<u8>, CharSumState>>();
+        assert_send::<OccupiedEntry<'_, String, Vec<u8>>>();
+        assert_send::<VacantEntry<'_, String, Vec<u8>, CharSumState>>();
+    }
+
+    #[test]
+    fn diff() {
+        let old: HashS2T<_, _> = [("a", 1), ("b", 2), ("ab", 3), ("ba", 4)]
+            .into_iter()
+            .collect();
+        let mut new = HashS2T::with_hasher(Fnv1aState::default());
+        new.extend([("b", 2), ("ab", 30), ("ba", 40), ("c", 5), ("ca", 6)]);
+
+        let mut diff = old.diff(&new);
+        diff.added.sort();
+        diff.changed.sort();
+        assert_eq!(diff.removed, [(&"a", &1)]);
+        assert_eq!(diff.added, [(&"c", &5), (&"ca", &6)]);
+        assert_eq!(diff.changed, [(&"ab", &3, &30), (&"ba", &4, &40)]);
+        assert!(!diff.is_empty());
+
+        // the other way round, added and removed swap
+        let mut back = new.diff(&old);
+        back.removed.sort();
+        back.changed.sort();
+        assert_eq!(back.removed, diff.added);
+        assert_eq!(back.added, diff.removed);
+        assert_eq!(back.changed, [(&"ab", &30, &3), (&"ba", &40, &4)]);
+        assert!(old.diff(&old.clone()).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+// A bounded cache: once it holds `capacity` entries, inserting a new key
+// evicts the least recently used one. Every insert or get stamps the key
+// with the next tick, and `recency` keeps the keys ordered by their last
+// tick, so the one to evict is always the first there.
+use alloc::collections::BTreeMap;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use crate::{CharSumState, HashS2T};
+
+pub struct LruHashS2T<K, V, S = CharSumState> {
+    // key -> (last tick it was used, value)
+    table: HashS2T<K, (u64, V), S>,
+    // last tick -> key, one for every entry
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+    capacity: usize,
+}
+
+impl<K, V> LruHashS2T<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_hasher(capacity, CharSumState::default())
+    }
+}
+
+impl<K, V, S> LruHashS2T<K, V, S> {
+    // Panics if capacity is 0: nothing could ever be inserted
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> Self {
+        assert!(capacity > 0, "LRU capacity must be at least 1");
+        Self {
+            table: HashS2T::with_capacity_and_hasher(capacity, hash_builder),
+            recency: BTreeMap::new(),
+            tick: 0,
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    // Entries it holds before evicting, not buckets
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+impl<K, V, S> LruHashS2T<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    // Moves the entry used at tick `last` to `tick`
+    fn touch(recency: &mut BTreeMap<u64, K>, last: &mut u64, tick: u64) {
+        let k = recency.remove(last).expect("every entry has a tick");
+        recency.insert(tick, k);
+        *last = tick;
+    }
+
+    // Overwriting counts as a use. A new key past the capacity evicts the
+    // least recently used entry first.
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        let tick = self.next_tick();
+        if let Some((last, old)) = self.table.get_mut(&k) {
+            Self::touch(&mut self.recency, last, tick);
+            return Some(core::mem::replace(old, v));
+        }
+        if self.table.len() == self.capacity {
+            let (_, oldest) = self.recency.pop_first().expect("a full cache has entries");
+            self.table.remove(&oldest);
+        }
+        self.recency.insert(tick, k.clone());
+        self.table.insert(k, (tick, v));
+        None
+    }
+
+    // Marks the entry as just used
+    pub fn get<Q>(&mut self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let tick = self.next_tick();
+        let (last, v) = self.table.get_mut(k)?;
+        Self::touch(&mut self.recency, last, tick);
+        Some(v)
+    }
+
+    // Like get, without counting as a use
+    pub fn peek<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get(k).map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.contains_key(k)
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (last, v) = self.table.remove(k)?;
+        self.recency.remove(&last);
+        Some(v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut h = LruHashS2T::new(3);
+        h.insert("Woffo", 1);
+        h.insert("Gato", 2);
+        h.insert("Pez", 3);
+        assert_eq!(h.len(), 3);
+
+        // Woffo was the oldest; using it leaves Gato as the victim
+        assert_eq!(h.get("Woffo"), Some(&1));
+        h.insert("Loro", 4);
+        assert_eq!(h.len(), 3);
+        assert!(!h.contains_key("Gato"));
+        assert_eq!(h.peek("Woffo"), Some(&1));
+
+        // overwriting is a use too, and peeking isn't: Woffo goes next
+        assert_eq!(h.insert("Pez", 30), Some(3));
+        assert_eq!(h.peek("Loro"), Some(&4));
+        h.get("Loro");
+        h.insert("Gato", 5);
+        assert!(!h.contains_key("Woffo"));
+        assert_eq!((h.peek("Pez"), h.peek("Loro")), (Some(&30), Some(&4)));
+        assert_eq!(h.recency.len(), h.len());
+    }
+
+    #[test]
+    fn peek_keeps_eviction_order() {
+        let mut h = LruHashS2T::new(2);
+        h.insert("Woffo", 1);
+        h.insert("Gato", 2);
+        let ticks = h.recency.clone();
+        for _ in 0..3 {
+            assert_eq!(h.peek("Woffo"), Some(&1));
+        }
+        assert_eq!(h.peek("Pez"), None);
+        assert_eq!(h.recency, ticks);
+        // Woffo is still the least recently used
+        h.insert("Pez", 3);
+        assert!(!h.contains_key("Woffo"));
+        assert_eq!(h.peek("Gato"), Some(&2));
+    }
+
+    #[test]
+    fn remove_frees_a_slot() {
+        let mut h = LruHashS2T::new(2);
+        h.insert(1, "a");
+        h.insert(2, "b");
+        assert_eq!(h.remove(&1), Some("a"));
+        assert_eq!(h.remove(&1), None);
+        h.insert(3, "c");
+        // room for 3 without evicting 2
+        assert_eq!((h.peek(&2), h.peek(&3)), (Some(&"b"), Some(&"c")));
+        assert_eq!(h.capacity(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "LRU capacity must be at least 1")]
+    fn zero_capacity() {
+        LruHashS2T::<u8, u8>::new(0);
+    }
+}
@@ -0,0 +1,109 @@
+// Rough memory estimate for a table: the bucket vector, the inner vectors
+// (by capacity, not len: that's what is allocated), and whatever the keys
+// own on the heap. Allocator overhead is left out.
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::{HashItem, HashNode, HashS2T};
+
+// Heap bytes owned by a value, not counting the value itself
+pub trait HeapSize {
+    fn heap_size(&self) -> usize;
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for Box<str> {
+    fn heap_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+// borrowed keys (like the &str ones) own nothing
+impl<T: ?Sized> HeapSize for &T {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+macro_rules! no_heap {
+    ($($t:ty),*) => {
+        $(
+            impl HeapSize for $t {
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+no_heap!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, char, bool);
+
+impl<K, V, S> HashS2T<K, V, S>
+where
+    K: HeapSize,
+{
+    // Values are counted for their inline size only
+    pub fn memory_usage(&self) -> usize {
+        let buckets = self.items.capacity() * size_of::<HashNode<Vec<HashItem<K, V>>>>();
+        let chains: usize = self
+            .items
+            .iter()
+            .flatten()
+            .map(|items| items.capacity() * size_of::<HashItem<K, V>>())
+            .sum();
+        let keys: usize = self.keys().map(HeapSize::heap_size).sum();
+        size_of::<Self>() + buckets + chains + keys
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn grows_with_entries() {
+        let mut h: HashS2T<String, u64> = HashS2T::with_capacity(3);
+        let mut last = h.memory_usage();
+        assert!(
+            last >= size_of::<HashS2T<String, u64>>()
+                + 4 * size_of::<HashNode<Vec<HashItem<String, u64>>>>()
+        );
+        for i in 0..1000 {
+            h.insert(format!("key_{i}"), i);
+            let now = h.memory_usage();
+            assert!(now >= last, "{now} < {last} after {i} inserts");
+            last = now;
+        }
+        // at least the key bytes and an item per entry
+        let key_bytes: usize = h.keys().map(String::len).sum();
+        assert!(last >= key_bytes + 1000 * size_of::<HashItem<String, u64>>());
+    }
+
+    #[test]
+    fn borrowed_keys_own_nothing() {
+        let mut owned: HashS2T<String, u8> = HashS2T::new();
+        let mut borrowed: HashS2T<&str, u8> = HashS2T::new();
+        let long = "a key long enough to be worth counting";
+        owned.insert(long.to_string(), 0);
+        borrowed.insert(long, 0);
+        assert!(owned.memory_usage() >= borrowed.memory_usage() + long.len());
+        assert_eq!(
+            vec![String::from("ab")].heap_size(),
+            size_of::<String>() + 2
+        );
+    }
+}
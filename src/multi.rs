@@ -0,0 +1,140 @@
+// A multimap: inserting a key that is already there adds another value
+// for it instead of overwriting. Each key holds its values in a Vec, in
+// the order they were inserted.
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use crate::{CharSumState, HashS2T};
+
+pub struct MultiHashS2T<K, V, S = CharSumState> {
+    table: HashS2T<K, Vec<V>, S>,
+    // values, not keys
+    len: usize,
+}
+
+impl<K, V, S> Default for MultiHashS2T<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self {
+            table: HashS2T::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<K, V> MultiHashS2T<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, S> MultiHashS2T<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            table: HashS2T::with_hasher(hash_builder),
+            len: 0,
+        }
+    }
+
+    // Number of values, counting every one under the same key
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn keys_len(&self) -> usize {
+        self.table.len()
+    }
+
+    // Every (key, value) pair, a key's values together and in order
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.table
+            .iter()
+            .flat_map(|item| item.value().iter().map(move |v| (item.key(), v)))
+    }
+}
+
+impl<K, V, S> MultiHashS2T<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn insert(&mut self, k: K, v: V) {
+        self.table.entry(k).or_default().push(v);
+        self.len += 1;
+    }
+
+    // In insertion order; nothing for a missing key
+    pub fn get_all<Q>(&self, k: &Q) -> impl Iterator<Item = &V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get(k).into_iter().flatten()
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.contains_key(k)
+    }
+
+    // Every value the key had, in insertion order (empty if it had none)
+    pub fn remove_all<Q>(&mut self, k: &Q) -> Vec<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let values = self.table.remove(k).unwrap_or_default();
+        self.len -= values.len();
+        values
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_key_three_times() {
+        let mut h = MultiHashS2T::new();
+        h.insert("Woffo", 1);
+        h.insert("Gato", 2);
+        h.insert("Woffo", 3);
+        h.insert("Woffo", 1);
+
+        assert_eq!(h.get_all("Woffo").collect::<Vec<_>>(), [&1, &3, &1]);
+        assert_eq!(h.get_all("Gato").collect::<Vec<_>>(), [&2]);
+        assert_eq!(h.get_all("Pez").count(), 0);
+        assert_eq!((h.len(), h.keys_len()), (4, 2));
+        assert_eq!(h.iter().count(), 4);
+
+        assert_eq!(h.remove_all("Woffo"), [1, 3, 1]);
+        assert!(!h.contains_key("Woffo"));
+        assert!(h.remove_all("Woffo").is_empty());
+        assert_eq!(h.len(), 1);
+        assert_eq!(h.iter().collect::<Vec<_>>(), [(&"Gato", &2)]);
+    }
+
+    #[test]
+    fn grouping() {
+        let mut by_len: MultiHashS2T<usize, &str> = MultiHashS2T::new();
+        for w in ["a", "bb", "cc", "d", "eee", "ff"] {
+            by_len.insert(w.len(), w);
+        }
+        assert_eq!(
+            by_len.get_all(&2).copied().collect::<Vec<_>>(),
+            ["bb", "cc", "ff"]
+        );
+        assert_eq!(by_len.get_all(&1).copied().collect::<Vec<_>>(), ["a", "d"]);
+        assert_eq!(by_len.keys_len(), 3);
+    }
+}
@@ -0,0 +1,218 @@
+// A table where keys that normalize the same (e.g. "Woffo" and "woffo",
+// when lowercasing) are the same key. Entries are stored under the
+// normalized key, next to the key as it was last written, which is what
+// get_key_value hands back.
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use core::hash::BuildHasher;
+
+use crate::{CharSumState, HashS2T};
+
+pub trait KeyNormalizer {
+    fn normalize<'a>(&self, k: &'a str) -> Cow<'a, str>;
+}
+
+// Lowercases A-Z only; every other char is left alone
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsciiLowercase;
+
+impl KeyNormalizer for AsciiLowercase {
+    fn normalize<'a>(&self, k: &'a str) -> Cow<'a, str> {
+        if k.bytes().any(|b| b.is_ascii_uppercase()) {
+            Cow::Owned(k.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(k)
+        }
+    }
+}
+
+// Full unicode lowercasing ("ÑANDÚ" and "ñandú" are the same key)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lowercase;
+
+impl KeyNormalizer for Lowercase {
+    fn normalize<'a>(&self, k: &'a str) -> Cow<'a, str> {
+        if k.chars().any(char::is_uppercase) {
+            Cow::Owned(k.to_lowercase())
+        } else {
+            Cow::Borrowed(k)
+        }
+    }
+}
+
+// Unicode NFC: canonically equivalent spellings, like "é" as one char or
+// as "e" and a combining accent, are the same key. Case is kept.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Nfc;
+
+#[cfg(feature = "unicode-normalization")]
+impl KeyNormalizer for Nfc {
+    fn normalize<'a>(&self, k: &'a str) -> Cow<'a, str> {
+        use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+        // most keys already are, and the quick check says so without
+        // allocating
+        if is_nfc_quick(k.chars()) == IsNormalized::Yes {
+            Cow::Borrowed(k)
+        } else {
+            Cow::Owned(k.nfc().collect())
+        }
+    }
+}
+
+pub struct NormalizedHashS2T<V, N = AsciiLowercase, S = CharSumState> {
+    // normalized key -> (key as written, value)
+    table: HashS2T<String, (String, V), S>,
+    normalizer: N,
+}
+
+impl<V> NormalizedHashS2T<V> {
+    pub fn case_insensitive() -> Self {
+        Self::new(AsciiLowercase)
+    }
+}
+
+impl<V, N> NormalizedHashS2T<V, N> {
+    pub fn new(normalizer: N) -> Self {
+        Self::with_normalizer_and_hasher(normalizer, CharSumState::default())
+    }
+}
+
+impl<V, N, S> NormalizedHashS2T<V, N, S> {
+    pub fn with_normalizer_and_hasher(normalizer: N, hash_builder: S) -> Self {
+        Self {
+            table: HashS2T::with_hasher(hash_builder),
+            normalizer,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    // Keys as last written
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &V)> {
+        self.table.values().map(|(k, v)| (k.as_str(), v))
+    }
+}
+
+impl<V, N, S> NormalizedHashS2T<V, N, S>
+where
+    N: KeyNormalizer,
+    S: BuildHasher,
+{
+    // Overwrites any key normalizing the same, casing included: the new
+    // spelling is the one kept
+    pub fn insert(&mut self, k: &str, v: V) -> Option<V> {
+        let normalized = self.normalizer.normalize(k).into_owned();
+        self.table
+            .insert(normalized, (k.to_string(), v))
+            .map(|(_, v)| v)
+    }
+
+    pub fn get(&self, k: &str) -> Option<&V> {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, k: &str) -> Option<&mut V> {
+        let normalized = self.normalizer.normalize(k);
+        self.table.get_mut(normalized.as_ref()).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value(&self, k: &str) -> Option<(&str, &V)> {
+        let normalized = self.normalizer.normalize(k);
+        self.table
+            .get(normalized.as_ref())
+            .map(|(k, v)| (k.as_str(), v))
+    }
+
+    pub fn contains_key(&self, k: &str) -> bool {
+        self.get(k).is_some()
+    }
+
+    pub fn remove(&mut self, k: &str) -> Option<V> {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    // The key comes back as last written, not as passed
+    pub fn remove_entry(&mut self, k: &str) -> Option<(String, V)> {
+        let normalized = self.normalizer.normalize(k);
+        self.table.remove(normalized.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mixed_case_collapses() {
+        let mut h = NormalizedHashS2T::case_insensitive();
+        assert_eq!(h.insert("Woffo", 1), None);
+        assert_eq!(h.insert("woffo", 2), Some(1));
+        assert_eq!(h.insert("WOFFO", 3), Some(2));
+        h.insert("Gato", 4);
+
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("wOfFo"), Some(&3));
+        // the last spelling written is the one kept
+        assert_eq!(h.get_key_value("woffo"), Some(("WOFFO", &3)));
+        assert_eq!(h.get_key_value("GATO"), Some(("Gato", &4)));
+
+        *h.get_mut("gato").unwrap() += 1;
+        assert_eq!(h.remove_entry("GATO"), Some(("Gato".to_string(), 5)));
+        h.insert("gato", 5);
+        assert_eq!(h.remove("GaTo"), Some(5));
+        assert!(!h.contains_key("Gato"));
+        assert_eq!(h.iter().collect::<Vec<_>>(), [("WOFFO", &3)]);
+    }
+
+    #[test]
+    fn ascii_leaves_other_chars() {
+        let mut h = NormalizedHashS2T::new(AsciiLowercase);
+        h.insert("ñandú", 1);
+        h.insert("ÑANDÚ", 2);
+        // only the ASCII letters were folded: "ñandú" vs "ÑandÚ"
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("ÑanDÚ"), Some(&2));
+    }
+
+    #[test]
+    fn unicode_lowercase() {
+        let mut h = NormalizedHashS2T::new(Lowercase);
+        h.insert("ñandú", 1);
+        h.insert("ÑANDÚ", 2);
+        assert_eq!(h.len(), 1);
+        assert_eq!(h.get_key_value("Ñandú"), Some(("ÑANDÚ", &2)));
+
+        assert_eq!(Lowercase.normalize("gato"), Cow::Borrowed("gato"));
+        assert_eq!(AsciiLowercase.normalize("ÑANDÚ"), "ÑandÚ");
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn nfc_collapses_equivalent_forms() {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        assert_ne!(composed, decomposed);
+
+        let mut h = NormalizedHashS2T::new(Nfc);
+        h.insert(decomposed, 1);
+        assert_eq!(h.insert(composed, 2), Some(1));
+        assert_eq!(h.len(), 1);
+        assert_eq!(h.get(decomposed), Some(&2));
+        // as written last
+        assert_eq!(h.get_key_value(decomposed), Some((composed, &2)));
+
+        assert_eq!(Nfc.normalize(composed), Cow::Borrowed(composed));
+        assert_eq!(Nfc.normalize(decomposed), composed);
+        // not a case folder
+        h.insert("CAF\u{c9}", 3);
+        assert_eq!(h.len(), 2);
+    }
+}
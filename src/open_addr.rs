@@ -0,0 +1,297 @@
+// Open Addressing counterpart of HashS2T: items live directly in a flat
+// vector of slots and collisions are resolved with linear probing (try
+// the next slot, wrapping around at the end) instead of inner vectors.
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use crate::{buckets_for, CharSumState, HashItem, DEFAULT_HASH2ST_SIZE, DEFAULT_MAX_LOAD_FACTOR};
+
+enum Slot<K, V> {
+    Empty,
+    // A removed item. Probing must go past it, since the keys after it
+    // may have been pushed there by the removed one.
+    Tombstone,
+    Full(HashItem<K, V>),
+}
+
+fn empty_slots<K, V>(n: usize) -> Vec<Slot<K, V>> {
+    let mut slots = Vec::with_capacity(n);
+    // vec![Slot::Empty; ...] requires Slot: Clone
+    for _ in 0..n {
+        slots.push(Slot::Empty);
+    }
+    slots
+}
+
+pub struct OpenAddr<K, V, S = CharSumState> {
+    slots: Vec<Slot<K, V>>,
+    len: usize,
+    tombstones: usize,
+    hash_builder: S,
+    // counts tombstones too: they make probe sequences longer just the same
+    max_load_factor: f64,
+}
+
+impl<K, V, S> Default for OpenAddr<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V> OpenAddr<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(n: usize) -> Self {
+        Self::with_slots_and_hasher(
+            buckets_for(n, DEFAULT_MAX_LOAD_FACTOR),
+            CharSumState::default(),
+        )
+    }
+}
+
+impl<K, V, S> OpenAddr<K, V, S> {
+    fn with_slots_and_hasher(n: usize, hash_builder: S) -> Self {
+        Self {
+            slots: empty_slots(n),
+            len: 0,
+            tombstones: 0,
+            hash_builder,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+        }
+    }
+
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_slots_and_hasher(DEFAULT_HASH2ST_SIZE, hash_builder)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HashItem<K, V>> {
+        self.slots.iter().filter_map(|slot| match slot {
+            Slot::Full(item) => Some(item),
+            _ => None,
+        })
+    }
+}
+
+impl<K, V, S> OpenAddr<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    // Slot the probe sequence for a key starts at
+    fn home<Q>(&self, k: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        self.hash_builder.hash_one(k) as usize % self.slots.len()
+    }
+
+    // Slots in probe order, starting at the key's home slot
+    fn probe<Q>(&self, k: &Q) -> impl Iterator<Item = usize>
+    where
+        Q: Hash + ?Sized,
+    {
+        let n = self.slots.len();
+        let home = if n == 0 { 0 } else { self.home(k) };
+        (0..n).map(move |step| (home + step) % n)
+    }
+
+    fn find<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        for i in self.probe(k) {
+            match &self.slots[i] {
+                Slot::Empty => return None,
+                Slot::Tombstone => continue,
+                Slot::Full(item) if item.k.borrow() == k => return Some(i),
+                Slot::Full(_) => continue,
+            }
+        }
+        None
+    }
+
+    // Rebuilds the slot vector, dropping every tombstone on the way
+    fn resize(&mut self, n: usize) {
+        let old = core::mem::replace(&mut self.slots, empty_slots(n));
+        self.tombstones = 0;
+        for slot in old {
+            if let Slot::Full(item) = slot {
+                let i = self
+                    .probe(&item.k)
+                    .find(|&i| matches!(self.slots[i], Slot::Empty))
+                    .expect("a new slot vector has room for every item");
+                self.slots[i] = Slot::Full(item);
+            }
+        }
+    }
+
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if let Some(i) = self.find(&k) {
+            return match &mut self.slots[i] {
+                Slot::Full(item) => Some(core::mem::replace(&mut item.v, v)),
+                _ => unreachable!("find only returns full slots"),
+            };
+        }
+        let n = self.slots.len();
+        if (self.len + self.tombstones + 1) as f64 > n as f64 * self.max_load_factor {
+            // dropping the tombstones may be enough, then the size stays
+            let needed = buckets_for(self.len + 1, self.max_load_factor);
+            self.resize(needed.max(n));
+        }
+        // reuse the first tombstone on the way, if any
+        let i = self
+            .probe(&k)
+            .find(|&i| !matches!(self.slots[i], Slot::Full(_)))
+            .expect("the load factor keeps free slots around");
+        if let Slot::Tombstone = self.slots[i] {
+            self.tombstones -= 1;
+        }
+        self.slots[i] = Slot::Full(HashItem { k, v });
+        self.len += 1;
+        None
+    }
+
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match &self.slots[self.find(k)?] {
+            Slot::Full(item) => Some(&item.v),
+            _ => None,
+        }
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(k).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let i = self.find(k)?;
+        match core::mem::replace(&mut self.slots[i], Slot::Tombstone) {
+            Slot::Full(HashItem { v, .. }) => {
+                self.len -= 1;
+                self.tombstones += 1;
+                Some(v)
+            }
+            _ => unreachable!("find only returns full slots"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // u8 keys hash to themselves with the char-sum hasher, so the home
+    // slot of k is k % slots
+    fn full_slot(h: &OpenAddr<u8, i32>, i: usize) -> Option<(u8, i32)> {
+        match &h.slots[i] {
+            Slot::Full(HashItem { k, v }) => Some((*k, *v)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let mut h = OpenAddr::new();
+        assert_eq!(h.insert("Woffo", 1), None);
+        assert_eq!(h.insert("Gato", 2), None);
+        assert_eq!(h.insert("Gato", 3), Some(2));
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("Woffo"), Some(&1));
+        assert_eq!(h.get("Gato"), Some(&3));
+        assert!(!h.contains_key("Pez"));
+
+        assert_eq!(h.remove("Woffo"), Some(1));
+        assert_eq!(h.remove("Woffo"), None);
+        assert_eq!(h.get("Woffo"), None);
+        assert_eq!(h.len(), 1);
+    }
+
+    #[test]
+    fn probe_wraparound() {
+        let mut h = OpenAddr::with_capacity(5);
+        assert_eq!(h.slots.len(), 8);
+        // all three start at the last slot
+        h.insert(7u8, 1);
+        h.insert(15, 2);
+        h.insert(23, 3);
+        assert_eq!(full_slot(&h, 7), Some((7, 1)));
+        assert_eq!(full_slot(&h, 0), Some((15, 2)));
+        assert_eq!(full_slot(&h, 1), Some((23, 3)));
+        assert_eq!(h.get(&23), Some(&3));
+        assert_eq!(h.get(&31), None);
+    }
+
+    #[test]
+    fn reinsert_into_tombstone() {
+        let mut h = OpenAddr::with_capacity(5);
+        h.insert(7u8, 1);
+        h.insert(15, 2);
+        h.insert(23, 3);
+
+        assert_eq!(h.remove(&15), Some(2));
+        assert!(matches!(h.slots[0], Slot::Tombstone));
+        assert_eq!(h.tombstones, 1);
+        // probing goes past the tombstone
+        assert_eq!(h.get(&23), Some(&3));
+
+        h.insert(31, 4);
+        assert_eq!(full_slot(&h, 0), Some((31, 4)));
+        assert_eq!(h.tombstones, 0);
+        assert_eq!(h.len(), 3);
+        assert_eq!(h.get(&7), Some(&1));
+        assert_eq!(h.get(&23), Some(&3));
+    }
+
+    #[test]
+    fn resize() {
+        let mut h = OpenAddr::new();
+        for i in 0..5000 {
+            h.insert(format!("key_{i}"), i);
+        }
+        assert_eq!(h.len(), 5000);
+        assert!(h.slots.len() as f64 * DEFAULT_MAX_LOAD_FACTOR >= 5000.0);
+        for i in 0..5000 {
+            assert_eq!(h.get(&format!("key_{i}")), Some(&i));
+        }
+    }
+
+    #[test]
+    fn tombstones_are_cleaned_up() {
+        let mut h = OpenAddr::with_capacity(5);
+        // insert/remove churn never grows the table, it rehashes in place
+        for i in 0..100u8 {
+            h.insert(i, i32::from(i));
+            h.remove(&i);
+        }
+        assert_eq!(h.slots.len(), 8);
+        assert!(h.is_empty());
+        assert!(h.tombstones < h.slots.len());
+        assert_eq!(h.iter().count(), 0);
+    }
+}
@@ -0,0 +1,342 @@
+// A table that iterates in insertion order, like indexmap. Every entry is
+// stamped with a sequence number when its key is first inserted, and
+// overwriting keeps the stamp, so the key keeps its place. Lookups stay as
+// fast as in the plain table.
+//
+// `order` lists the keys by stamp: the key stamped seq sits at
+// seq - first_seq, and a removed key leaves a None behind. Iterating walks
+// it and looks each key up, so it's O(n) plus the Nones left over. Nones at either end are popped straight away, so both ends are
+// always entries and reading them is O(1). The Nones in the middle are
+// squeezed out once they outnumber the entries, restamping every entry:
+// O(n), but paid for by the n removals it took to get there.
+use alloc::collections::{vec_deque, VecDeque};
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use crate::{CharSumState, HashS2T};
+
+pub struct OrderedHashS2T<K, V, S = CharSumState> {
+    // key -> (sequence number, value)
+    table: HashS2T<K, (u64, V), S>,
+    // keys by sequence number, starting at first_seq; None for the
+    // removed ones, never at the ends
+    order: VecDeque<Option<K>>,
+    first_seq: u64,
+}
+
+impl<K, V, S> Default for OrderedHashS2T<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self {
+            table: HashS2T::default(),
+            order: VecDeque::new(),
+            first_seq: 0,
+        }
+    }
+}
+
+impl<K, V> OrderedHashS2T<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, S> OrderedHashS2T<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self {
+            table: HashS2T::with_hasher(hash_builder),
+            order: VecDeque::new(),
+            first_seq: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+impl<K, V, S> OrderedHashS2T<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    S: BuildHasher,
+{
+    // In the order the keys were first inserted
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.order.iter().flatten().map(|k| {
+            let (k, (_, v)) = self
+                .table
+                .get_key_value(k)
+                .expect("order only lists entries");
+            (k, v)
+        })
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    // A new key goes last; an existing one keeps its place
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if let Some((_, old)) = self.table.get_mut(&k) {
+            return Some(core::mem::replace(old, v));
+        }
+        let seq = self.first_seq + self.order.len() as u64;
+        self.order.push_back(Some(k.clone()));
+        self.table.insert(k, (seq, v));
+        None
+    }
+
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get(k).map(|(_, v)| v)
+    }
+
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.get_mut(k).map(|(_, v)| v)
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.table.contains_key(k)
+    }
+
+    // The other keys keep their order; inserting the key again puts it
+    // last
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (seq, v) = self.table.remove(k)?;
+        self.order[(seq - self.first_seq) as usize] = None;
+        while let Some(None) = self.order.front() {
+            self.order.pop_front();
+            self.first_seq += 1;
+        }
+        while let Some(None) = self.order.back() {
+            self.order.pop_back();
+        }
+        if self.order.len() > 2 * self.table.len() {
+            self.squeeze_order();
+        }
+        Some(v)
+    }
+
+    // Drops the Nones in the middle of order, restamping the entries from
+    // first_seq up; their relative order stays the same
+    fn squeeze_order(&mut self) {
+        let keys = core::mem::take(&mut self.order);
+        for (seq, k) in (self.first_seq..).zip(keys.into_iter().flatten()) {
+            self.table.get_mut(&k).expect("order only lists entries").0 = seq;
+            self.order.push_back(Some(k));
+        }
+    }
+
+    fn entry_at(&self, k: Option<&Option<K>>) -> Option<(&K, &V)> {
+        let k = k?
+            .as_ref()
+            .expect("the ends of order are never removed keys");
+        self.table.get_key_value(k).map(|(k, (_, v))| (k, v))
+    }
+
+    // The entry inserted longest ago; an overwrite doesn't make it newer.
+    // O(1): the front of order, then a lookup.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.entry_at(self.order.front())
+    }
+
+    // The entry inserted last
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.entry_at(self.order.back())
+    }
+}
+
+// Walks order, taking each key's entry out of the table
+pub struct IntoIter<K, V, S> {
+    order: vec_deque::IntoIter<Option<K>>,
+    table: HashS2T<K, (u64, V), S>,
+}
+
+impl<K, V, S> Iterator for IntoIter<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let k = self.order.by_ref().flatten().next()?;
+        let (k, (_, v)) = self
+            .table
+            .remove_entry(&k)
+            .expect("order only lists entries");
+        Some((k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.table.len(), Some(self.table.len()))
+    }
+}
+
+impl<K, V, S> IntoIterator for OrderedHashS2T<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, S>;
+
+    // In insertion order, like iter
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            order: self.order.into_iter(),
+            table: self.table,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Every entry is in order at its stamp, the ends are entries, and
+    // the Nones don't outnumber the entries
+    fn check_order<K: Hash + Eq + Clone, V>(h: &OrderedHashS2T<K, V>) {
+        for (i, k) in h.order.iter().enumerate() {
+            if let Some(k) = k {
+                assert_eq!(h.table.get(k).unwrap().0, h.first_seq + i as u64);
+            }
+        }
+        assert_eq!(h.order.iter().flatten().count(), h.len());
+        assert!(!matches!(h.order.front(), Some(None)));
+        assert!(!matches!(h.order.back(), Some(None)));
+        assert!(h.order.len() <= 2 * h.len());
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut h = OrderedHashS2T::new();
+        // "ab" and "ba" share a bucket, in the plain table "ba" would
+        // come first
+        for (k, v) in [("Woffo", 1), ("ba", 2), ("Gato", 3), ("ab", 4), ("Pez", 5)] {
+            h.insert(k, v);
+        }
+        assert_eq!(h.insert("Gato", 30), Some(3));
+        assert_eq!(
+            h.iter().collect::<Vec<_>>(),
+            [
+                (&"Woffo", &1),
+                (&"ba", &2),
+                (&"Gato", &30),
+                (&"ab", &4),
+                (&"Pez", &5)
+            ]
+        );
+
+        assert_eq!(h.remove("ba"), Some(2));
+        h.insert("ba", 20);
+        *h.get_mut("Woffo").unwrap() += 10;
+        assert_eq!(
+            h.keys().collect::<Vec<_>>(),
+            [&"Woffo", &"Gato", &"ab", &"Pez", &"ba"]
+        );
+        assert_eq!(
+            h.into_iter().collect::<Vec<_>>(),
+            [
+                ("Woffo", 11),
+                ("Gato", 30),
+                ("ab", 4),
+                ("Pez", 5),
+                ("ba", 20)
+            ]
+        );
+    }
+
+    #[test]
+    fn many_keys_keep_order() {
+        let mut h = OrderedHashS2T::new();
+        for i in (0..2000).rev() {
+            h.insert(i, i * 2);
+        }
+        assert_eq!(h.len(), 2000);
+        assert!(h.keys().copied().eq((0..2000).rev()));
+        assert_eq!(h.get(&7), Some(&14));
+        assert!(h.contains_key(&1999));
+        assert!(h.values().copied().eq((0..2000).rev().map(|i| i * 2)));
+    }
+
+    #[test]
+    fn first_and_last() {
+        let mut h = OrderedHashS2T::new();
+        assert_eq!((h.first(), h.last()), (None, None));
+        h.insert("Woffo", 1);
+        assert_eq!(
+            (h.first(), h.last()),
+            (Some((&"Woffo", &1)), Some((&"Woffo", &1)))
+        );
+
+        h.insert("Gato", 2);
+        h.insert("Pez", 3);
+        // an overwrite keeps its place
+        h.insert("Woffo", 10);
+        assert_eq!(h.first(), Some((&"Woffo", &10)));
+        assert_eq!(h.last(), Some((&"Pez", &3)));
+
+        h.remove("Woffo");
+        h.remove("Pez");
+        assert_eq!(
+            (h.first(), h.last()),
+            (Some((&"Gato", &2)), Some((&"Gato", &2)))
+        );
+        h.insert("Woffo", 4);
+        assert_eq!(h.last(), Some((&"Woffo", &4)));
+        check_order(&h);
+    }
+
+    #[test]
+    fn removals_squeeze_order() {
+        let mut h = OrderedHashS2T::new();
+        for i in 0..1000 {
+            h.insert(i, i);
+        }
+        // from the middle out, so the ends stay put
+        for i in (1..999).filter(|i| i % 10 != 0) {
+            assert_eq!(h.remove(&i), Some(i));
+            check_order(&h);
+        }
+        assert_eq!(h.len(), 101);
+        assert_eq!((h.first(), h.last()), (Some((&0, &0)), Some((&999, &999))));
+        assert!(h
+            .keys()
+            .copied()
+            .eq((0..1000).filter(|i| i % 10 == 0).chain([999])));
+
+        for i in 0..1000 {
+            h.remove(&i);
+        }
+        assert!(h.order.is_empty());
+        h.insert(5, 5);
+        assert_eq!((h.first(), h.last()), (Some((&5, &5)), Some((&5, &5))));
+        check_order(&h);
+    }
+}
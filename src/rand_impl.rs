@@ -0,0 +1,72 @@
+// Random entries with rand. Picking a random bucket and then a random
+// item in it would favour the items of short buckets (a lone item would
+// come up as often as a whole bucket of them), so this draws a position
+// among all the entries instead and walks the buckets to it, skipping
+// each one by its length.
+use rand::{Rng, RngExt};
+
+use crate::{CollisionStrategy, HashItem, HashS2T};
+
+impl<K, V, S, B> HashS2T<K, V, S, B>
+where
+    B: CollisionStrategy<K, V>,
+{
+    // Every entry equally likely; None when empty
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<(&K, &V)> {
+        if self.len == 0 {
+            return None;
+        }
+        let mut i = rng.random_range(0..self.len);
+        for bucket in self.items.iter().flatten() {
+            if i < bucket.len() {
+                return bucket.iter().nth(i).map(|HashItem { k, v }| (k, v));
+            }
+            i -= bucket.len();
+        }
+        unreachable!("len counts every item in the buckets")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::LinkedList;
+
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::CharSumState;
+
+    #[test]
+    fn uniform() {
+        let mut rng = SmallRng::seed_from_u64(0x5eed);
+        assert_eq!(HashS2T::<u8, u8>::new().sample(&mut rng), None);
+
+        // the anagrams share a bucket, the rest have one each
+        let keys = ["abc", "cba", "bca", "Woffo", "Gato", "Pez"];
+        let mut h = HashS2T::new();
+        for (i, k) in keys.iter().enumerate() {
+            h.insert(*k, i);
+        }
+        assert_eq!(h.stats().max_bucket_len, 3);
+        let draws = 60_000;
+        let mut counts = [0; 6];
+        for _ in 0..draws {
+            let (k, &v) = h.sample(&mut rng).unwrap();
+            assert_eq!(keys[v], *k);
+            counts[v] += 1;
+        }
+        // 10000 each, give or take 3%: about 3.5 standard deviations
+        for (k, count) in keys.iter().zip(counts) {
+            assert!((9700..=10300).contains(&count), "{k}: {count}");
+        }
+    }
+
+    #[test]
+    fn any_strategy() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let mut h = HashS2T::<u32, u32, CharSumState, LinkedList<HashItem<u32, u32>>>::default();
+        h.insert(7, 49);
+        assert_eq!(h.sample(&mut rng), Some((&7, &49)));
+    }
+}
@@ -0,0 +1,43 @@
+// Parallel iteration with rayon: the bucket vector is split between the
+// threads, and each one walks the items of its buckets in order
+use rayon::prelude::*;
+
+use crate::{HashItem, HashS2T};
+
+impl<K, V, S> HashS2T<K, V, S>
+where
+    K: Sync,
+    V: Sync,
+{
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (&K, &V)> {
+        self.items
+            .par_iter()
+            .flat_map_iter(|node| node.iter().flatten().map(|HashItem { k, v }| (k, v)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn par_sum() {
+        let h: HashS2T<u64, u64> = (0..100_000).map(|i| (i, i * 3)).collect();
+        let sequential: u64 = h.values().sum();
+        let parallel: u64 = h.par_iter().map(|(_, v)| v).sum();
+        assert_eq!(parallel, sequential);
+        assert_eq!(h.par_iter().count(), h.len());
+    }
+
+    #[test]
+    fn par_iter_keys() {
+        let mut h = HashS2T::new();
+        h.insert("ab", 1);
+        // same bucket
+        h.insert("ba", 2);
+        h.insert("Woffo", 3);
+        let mut keys: Vec<_> = h.par_iter().map(|(k, _)| *k).collect();
+        keys.sort();
+        assert_eq!(keys, ["Woffo", "ab", "ba"]);
+    }
+}
@@ -0,0 +1,337 @@
+// Open Addressing with Robin Hood probing. Every slot remembers how far its
+// item is from its home slot; while probing for a free slot, an item that
+// is further from home than the one in the way takes that slot, and the
+// displaced one keeps probing. Probe lengths end up evened out, so the
+// longest one stays short even with a badly clustered hash.
+//
+// Removing shifts the following items back one slot instead of leaving a
+// tombstone, so the table never fills up with dead slots.
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+
+use crate::{buckets_for, CharSumState, HashItem, DEFAULT_HASH2ST_SIZE, DEFAULT_MAX_LOAD_FACTOR};
+
+struct Slot<K, V> {
+    // slots between the item's home slot and this one
+    dist: usize,
+    item: HashItem<K, V>,
+}
+
+fn empty_slots<K, V>(n: usize) -> Vec<Option<Slot<K, V>>> {
+    let mut slots = Vec::with_capacity(n);
+    // vec![None; ...] requires Slot: Clone
+    for _ in 0..n {
+        slots.push(None);
+    }
+    slots
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProbeStats {
+    pub entries: usize,
+    pub slots: usize,
+    // probes past the home slot it takes to reach an item, worst and mean;
+    // 0 and 0.0 when there are no items
+    pub max_probe_distance: usize,
+    pub avg_probe_distance: f64,
+}
+
+pub struct RobinHood<K, V, S = CharSumState> {
+    slots: Vec<Option<Slot<K, V>>>,
+    len: usize,
+    hash_builder: S,
+    max_load_factor: f64,
+}
+
+impl<K, V, S> Default for RobinHood<K, V, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V> RobinHood<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(n: usize) -> Self {
+        Self::with_slots_and_hasher(
+            buckets_for(n, DEFAULT_MAX_LOAD_FACTOR),
+            CharSumState::default(),
+        )
+    }
+}
+
+impl<K, V, S> RobinHood<K, V, S> {
+    fn with_slots_and_hasher(n: usize, hash_builder: S) -> Self {
+        Self {
+            slots: empty_slots(n),
+            len: 0,
+            hash_builder,
+            max_load_factor: DEFAULT_MAX_LOAD_FACTOR,
+        }
+    }
+
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self::with_slots_and_hasher(DEFAULT_HASH2ST_SIZE, hash_builder)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HashItem<K, V>> {
+        self.slots.iter().flatten().map(|slot| &slot.item)
+    }
+
+    pub fn stats(&self) -> ProbeStats {
+        let dists = || self.slots.iter().flatten().map(|slot| slot.dist);
+        ProbeStats {
+            entries: self.len,
+            slots: self.slots.len(),
+            max_probe_distance: dists().max().unwrap_or(0),
+            avg_probe_distance: if self.len == 0 {
+                0.0
+            } else {
+                dists().sum::<usize>() as f64 / self.len as f64
+            },
+        }
+    }
+}
+
+impl<K, V, S> RobinHood<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn home<Q>(&self, k: &Q) -> usize
+    where
+        Q: Hash + ?Sized,
+    {
+        self.hash_builder.hash_one(k) as usize % self.slots.len()
+    }
+
+    fn find<Q>(&self, k: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let n = self.slots.len();
+        if n == 0 {
+            return None;
+        }
+        let mut i = self.home(k);
+        for dist in 0..n {
+            match &self.slots[i] {
+                None => return None,
+                // the key would have taken this slot when it was inserted
+                Some(slot) if slot.dist < dist => return None,
+                Some(slot) if slot.item.k.borrow() == k => return Some(i),
+                Some(_) => {}
+            }
+            i = (i + 1) % n;
+        }
+        None
+    }
+
+    // Places an item whose key is not in the table yet; there must be a
+    // free slot
+    fn place(&mut self, item: HashItem<K, V>) {
+        let n = self.slots.len();
+        let mut i = self.home(&item.k);
+        let mut moving = Slot { dist: 0, item };
+        loop {
+            match &mut self.slots[i] {
+                None => {
+                    self.slots[i] = Some(moving);
+                    return;
+                }
+                // take from the rich (close to home), give to the poor
+                Some(slot) if slot.dist < moving.dist => core::mem::swap(slot, &mut moving),
+                Some(_) => {}
+            }
+            i = (i + 1) % n;
+            moving.dist += 1;
+        }
+    }
+
+    fn resize(&mut self, n: usize) {
+        let old = core::mem::replace(&mut self.slots, empty_slots(n));
+        for slot in old.into_iter().flatten() {
+            self.place(slot.item);
+        }
+    }
+
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        if let Some(i) = self.find(&k) {
+            let slot = self.slots[i]
+                .as_mut()
+                .expect("find only returns full slots");
+            return Some(core::mem::replace(&mut slot.item.v, v));
+        }
+        let n = self.slots.len();
+        if (self.len + 1) as f64 > n as f64 * self.max_load_factor {
+            self.resize(buckets_for(self.len + 1, self.max_load_factor).max(n * 2));
+        }
+        self.place(HashItem { k, v });
+        self.len += 1;
+        None
+    }
+
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.slots[self.find(k)?].as_ref()?;
+        Some(&slot.item.v)
+    }
+
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(k).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let n = self.slots.len();
+        let mut i = self.find(k)?;
+        let removed = self.slots[i].take().expect("find only returns full slots");
+        self.len -= 1;
+        // backward shift: pull the following items one slot closer to
+        // home, until an empty slot or one already at home
+        loop {
+            let next = (i + 1) % n;
+            match self.slots[next].take() {
+                Some(mut slot) if slot.dist > 0 => {
+                    slot.dist -= 1;
+                    self.slots[i] = Some(slot);
+                    i = next;
+                }
+                other => {
+                    self.slots[next] = other;
+                    break;
+                }
+            }
+        }
+        Some(removed.item.v)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Every item is `dist` slots past its home, and no item is more than
+    // one slot further from home than the one before it
+    fn check_invariants<K: Hash + Eq, V>(h: &RobinHood<K, V>) {
+        let n = h.slots.len();
+        for (i, slot) in h.slots.iter().enumerate() {
+            let Some(slot) = slot else { continue };
+            assert_eq!((h.home(&slot.item.k) + slot.dist) % n, i);
+            let prev = h.slots[(i + n - 1) % n].as_ref().map_or(0, |s| s.dist);
+            assert!(slot.dist <= prev + 1);
+        }
+        assert_eq!(h.iter().count(), h.len());
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let mut h = RobinHood::new();
+        assert_eq!(h.insert("Woffo", 1), None);
+        assert_eq!(h.insert("Gato", 2), None);
+        assert_eq!(h.insert("Gato", 3), Some(2));
+        assert_eq!(h.len(), 2);
+        assert_eq!(h.get("Woffo"), Some(&1));
+        assert_eq!(h.get("Gato"), Some(&3));
+        assert!(!h.contains_key("Pez"));
+
+        assert_eq!(h.remove("Woffo"), Some(1));
+        assert_eq!(h.remove("Woffo"), None);
+        assert_eq!(h.len(), 1);
+        check_invariants(&h);
+    }
+
+    #[test]
+    fn adversarial_cluster() {
+        // u32 keys hash to the sum of their bytes: 1..=20 get a home slot
+        // each, and the rest all go home to slot 0
+        let mut h = RobinHood::with_capacity(40);
+        assert_eq!(h.slots.len(), 64);
+        let colliding: Vec<u32> = (21..).filter(|k| h.home(k) == 0).take(20).collect();
+        for k in 1..=20 {
+            h.insert(k, k);
+        }
+        for &k in &colliding {
+            h.insert(k, k);
+        }
+        check_invariants(&h);
+
+        // Linear probing would put the colliding keys after 1..=20, the
+        // last one 39 slots from home. Here the 20 of them sit at 0..=19
+        // and 1..=20 move to 20..=39, 19 slots from home each.
+        let stats = h.stats();
+        assert_eq!(stats.entries, 40);
+        assert_eq!(stats.max_probe_distance, 19);
+        assert_eq!(stats.avg_probe_distance, (190 + 20 * 19) as f64 / 40.0);
+        for k in (1..=20).chain(colliding.iter().copied()) {
+            assert_eq!(h.get(&k), Some(&k));
+        }
+    }
+
+    #[test]
+    fn backward_shift_delete() {
+        let mut h = RobinHood::with_capacity(40);
+        let colliding: Vec<u32> = (1..).filter(|k| h.home(k) == 3).take(5).collect();
+        for &k in &colliding {
+            h.insert(k, k);
+        }
+        assert_eq!(h.stats().max_probe_distance, 4);
+
+        assert_eq!(h.remove(&colliding[0]), Some(colliding[0]));
+        // no tombstone: the rest moved one slot back
+        assert_eq!(h.stats().max_probe_distance, 3);
+        assert!(h.slots[7].is_none());
+        check_invariants(&h);
+        for &k in &colliding[1..] {
+            assert_eq!(h.get(&k), Some(&k));
+        }
+        assert_eq!(h.get(&colliding[0]), None);
+    }
+
+    #[test]
+    fn churn_keeps_probes_short() {
+        let mut h = RobinHood::new();
+        for i in 0..5000 {
+            h.insert(format!("key_{i}"), i);
+        }
+        for i in (0..5000).step_by(2) {
+            assert_eq!(h.remove(&format!("key_{i}")), Some(i));
+        }
+        check_invariants(&h);
+        assert_eq!(h.len(), 2500);
+        for i in 0..5000 {
+            let expected = if i % 2 == 0 { None } else { Some(&i) };
+            assert_eq!(h.get(&format!("key_{i}")), expected);
+        }
+        let empty = RobinHood::<u8, u8>::new().stats();
+        assert_eq!(
+            (empty.max_probe_distance, empty.avg_probe_distance),
+            (0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,108 @@
+// A table is (de)serialized as a plain map: the bucket layout depends
+// on the hasher and capacity, so it is rebuilt when loading instead of
+// being trusted from the input
+use core::fmt;
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{HashItem, HashS2T};
+
+impl<K, V, S> Serialize for HashS2T<K, V, S>
+where
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut map = serializer.serialize_map(Some(self.len))?;
+        for HashItem { k, v } in self.iter() {
+            map.serialize_entry(k, v)?;
+        }
+        map.end()
+    }
+}
+
+struct HashS2TVisitor<K, V, S> {
+    marker: PhantomData<HashS2T<K, V, S>>,
+}
+
+impl<'de, K, V, S> Visitor<'de> for HashS2TVisitor<K, V, S>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    type Value = HashS2T<K, V, S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut access: M) -> Result<Self::Value, M::Error> {
+        let n = access.size_hint().unwrap_or(0);
+        let mut h = HashS2T::with_capacity_and_hasher(n, S::default());
+        while let Some((k, v)) = access.next_entry()? {
+            h.insert(k, v);
+        }
+        Ok(h)
+    }
+}
+
+impl<'de, K, V, S> Deserialize<'de> for HashS2T<K, V, S>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    V: Deserialize<'de>,
+    S: BuildHasher + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(HashS2TVisitor {
+            marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        h.insert("Woffo".to_string(), 1);
+        h.insert("Gato".to_string(), 2);
+        h.insert("pingüino".to_string(), 3);
+        h.insert("🐕".to_string(), 4);
+
+        let json = serde_json::to_string(&h).unwrap();
+        let back: HashS2T<String, i32> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(back.len(), 4);
+        for HashItem { k, v } in h.iter() {
+            assert_eq!(back.get(k), Some(v));
+        }
+    }
+
+    #[test]
+    fn serializes_as_map() {
+        let mut h: HashS2T<String, i32> = HashS2T::new();
+        h.insert("ñandú".to_string(), 7);
+
+        // no buckets or indices in the output
+        assert_eq!(serde_json::to_string(&h).unwrap(), r#"{"ñandú":7}"#);
+    }
+
+    #[test]
+    fn deserialize_rebuilds_layout() {
+        let json = r#"{"a": 1, "b": 2, "ab": 3, "a": 4}"#;
+        let h: HashS2T<String, i32> = serde_json::from_str(json).unwrap();
+
+        // repeated keys behave like repeated inserts
+        assert_eq!(h.len(), 3);
+        assert_eq!(h.get("a"), Some(&4));
+        assert_eq!(h.get("ab"), Some(&3));
+        assert_eq!(h.bucket("ab"), h.bucket("ba"));
+    }
+}
@@ -0,0 +1,149 @@
+// Copy-on-write snapshots of a table. Taking a snapshot only bumps a
+// reference count; the table is cloned the first time one of the snapshots
+// sharing it changes something, and only that snapshot gets the copy.
+// Reads go through Deref straight to whichever table it holds.
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::ops::Deref;
+
+use crate::{CharSumState, HashS2T};
+
+pub struct CowSnapshot<K, V, S = CharSumState> {
+    table: Arc<HashS2T<K, V, S>>,
+}
+
+// Not derived: sharing the table needs no Clone bounds
+impl<K, V, S> Clone for CowSnapshot<K, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            table: Arc::clone(&self.table),
+        }
+    }
+}
+
+impl<K, V, S> From<HashS2T<K, V, S>> for CowSnapshot<K, V, S> {
+    fn from(table: HashS2T<K, V, S>) -> Self {
+        Self::new(table)
+    }
+}
+
+impl<K, V, S> Deref for CowSnapshot<K, V, S> {
+    type Target = HashS2T<K, V, S>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.table
+    }
+}
+
+impl<K, V, S> CowSnapshot<K, V, S> {
+    pub fn new(table: HashS2T<K, V, S>) -> Self {
+        Self {
+            table: Arc::new(table),
+        }
+    }
+
+    // Same as clone, under the name it's used for
+    pub fn snapshot(&self) -> Self {
+        self.clone()
+    }
+
+    // Whether both still read the same table, i.e. neither has changed
+    // since one was taken from the other
+    pub fn shares_table(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.table, &other.table)
+    }
+}
+
+impl<K, V, S> CowSnapshot<K, V, S>
+where
+    K: Clone,
+    V: Clone,
+    S: Clone,
+{
+    // Clones the table first if another snapshot shares it
+    pub fn to_mut(&mut self) -> &mut HashS2T<K, V, S> {
+        Arc::make_mut(&mut self.table)
+    }
+
+    // Clones the table only if another snapshot shares it
+    pub fn into_table(self) -> HashS2T<K, V, S> {
+        Arc::unwrap_or_clone(self.table)
+    }
+}
+
+impl<K, V, S> CowSnapshot<K, V, S>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    S: BuildHasher + Clone,
+{
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.to_mut().insert(k, v)
+    }
+
+    // A missing key changes nothing, so it doesn't clone anything either
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.table.contains_key(k) {
+            return None;
+        }
+        self.to_mut().get_mut(k)
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.table.contains_key(k) {
+            return None;
+        }
+        self.to_mut().remove(k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clones_on_first_write() {
+        let mut h = HashS2T::new();
+        h.insert("Woffo", 1);
+        h.insert("Gato", 2);
+        let original = CowSnapshot::from(h);
+
+        let mut snap = original.snapshot();
+        assert!(snap.shares_table(&original));
+        // reads go to the shared table
+        assert_eq!(snap.get("Woffo"), Some(&1));
+        assert!(core::ptr::eq(
+            snap.get("Gato").unwrap(),
+            original.get("Gato").unwrap()
+        ));
+        // and so do writes that change nothing
+        assert_eq!(snap.remove("Pez"), None);
+        assert_eq!(snap.get_mut("Pez"), None);
+        assert!(snap.shares_table(&original));
+
+        snap.insert("Pez", 3);
+        *snap.get_mut("Woffo").unwrap() += 10;
+        assert_eq!(snap.remove("Gato"), Some(2));
+        assert!(!snap.shares_table(&original));
+
+        assert_eq!(snap.len(), 2);
+        assert_eq!(snap.get("Woffo"), Some(&11));
+        assert_eq!(original.len(), 2);
+        assert_eq!(original.get("Woffo"), Some(&1));
+        assert_eq!(original.get("Gato"), Some(&2));
+        assert!(!original.contains_key("Pez"));
+
+        // the last one holding a table gets it back without a copy
+        let table = original.into_table();
+        assert_eq!(table.get("Gato"), Some(&2));
+    }
+}